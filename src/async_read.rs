@@ -0,0 +1,274 @@
+//! A runtime-agnostic async version of [`Read`](crate::Read)
+
+use crate::{
+    read::{prepare_to_end, probe_to_end},
+    ReadArray, ReadBufRef, ReadVec,
+};
+use std::{
+    future::Future,
+    io,
+    marker::PhantomData,
+    ops::DerefMut,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Read bytes asynchronously into a [`ReadBuf`](crate::ReadBuf).
+///
+/// This is the async equivalent of [`Read`](crate::Read). It is not tied to any runtime: with the `tokio` and
+/// `futures-io` features, [`Compat`] converts between this trait and the `AsyncRead` trait of that crate, in both
+/// directions.
+pub trait AsyncRead {
+    /// Attempt to pull some bytes from this source into the specified buffer.
+    ///
+    /// On success, returns `Poll::Ready(Ok(()))` and adds the bytes that were read to the filled region of `buf`.
+    /// If no bytes were added, the source has reached EOF or `buf` has no remaining capacity.
+    ///
+    /// If no data is available for reading, returns `Poll::Pending` and arranges for the current task to be woken
+    /// when the source becomes readable.
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufRef<'_>,
+    ) -> Poll<io::Result<()>>;
+}
+
+impl<R: AsyncRead + Unpin + ?Sized> AsyncRead for &mut R {
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufRef<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut **self.get_mut()).poll_read_buf(cx, buf)
+    }
+}
+
+impl<R: AsyncRead + Unpin + ?Sized> AsyncRead for Box<R> {
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufRef<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut **self.get_mut()).poll_read_buf(cx, buf)
+    }
+}
+
+impl<P> AsyncRead for Pin<P>
+where
+    P: DerefMut + Unpin,
+    P::Target: AsyncRead,
+{
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufRef<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.get_mut().as_mut().poll_read_buf(cx, buf)
+    }
+}
+
+/// An adapter between [`AsyncRead`] and the `AsyncRead` trait of an async runtime.
+///
+/// `Rt` is a marker for the runtime, which keeps the adapters for different runtimes from overlapping. Use the
+/// `Compat` alias in the `tokio` or `futures_io` module, which implements [`AsyncRead`] when `R` implements the
+/// runtime's trait, and the runtime's trait when `R` implements [`AsyncRead`].
+#[derive(Debug)]
+pub struct Compat<R, Rt> {
+    inner: R,
+    runtime: PhantomData<Rt>,
+}
+
+impl<R, Rt> Compat<R, Rt> {
+    /// Wraps `inner` in an adapter.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            runtime: PhantomData,
+        }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwraps this `Compat`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    #[cfg(any(feature = "tokio", feature = "futures-io"))]
+    pub(crate) fn project(self: Pin<&mut Self>) -> Pin<&mut R> {
+        // SAFETY: `inner` is structurally pinned. It is never moved out of a pinned `Compat`, and `Compat` is only
+        // `Unpin` when `R` is, since the runtime markers are always `Unpin`.
+        unsafe { self.map_unchecked_mut(|compat| &mut compat.inner) }
+    }
+}
+
+/// An extension of [`AsyncRead`] that provides futures for common reads.
+///
+/// The returned futures are cancellation safe: bytes that have been read are added to the filled region of the
+/// buffer straight away, so they stay there if the future is dropped before completing.
+pub trait AsyncReadExt: AsyncRead {
+    /// Pull some bytes from this source into the specified buffer.
+    ///
+    /// This is the async equivalent of [`Read::read_buf`](crate::Read::read_buf).
+    fn read_buf<'a>(&'a mut self, buf: ReadBufRef<'a>) -> ReadBufFuture<'a, &'a mut Self>
+    where
+        Self: Unpin,
+    {
+        ReadBufFuture::new(self, buf)
+    }
+
+    /// Read the exact number of bytes required to fill `buf`.
+    ///
+    /// This is the async equivalent of [`Read::read_buf_exact`](crate::Read::read_buf_exact).
+    fn read_buf_exact<'a>(&'a mut self, buf: ReadBufRef<'a>) -> ReadBufExact<'a, &'a mut Self>
+    where
+        Self: Unpin,
+    {
+        ReadBufExact::new(self, buf)
+    }
+
+    /// Read all bytes until EOF in this source, appending them to `buf`.
+    ///
+    /// This is the async equivalent of [`Read::read_buf_to_end`](crate::Read::read_buf_to_end).
+    fn read_buf_to_end<'a>(
+        &'a mut self,
+        buf: &'a mut ReadVec,
+        limit: Option<usize>,
+    ) -> ReadBufToEnd<'a, &'a mut Self>
+    where
+        Self: Unpin,
+    {
+        ReadBufToEnd::new(self, buf, limit)
+    }
+}
+
+impl<R: AsyncRead + ?Sized> AsyncReadExt for R {}
+
+/// Future for the [`read_buf`](AsyncReadExt::read_buf) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadBufFuture<'a, R> {
+    reader: R,
+    buf: ReadBufRef<'a>,
+}
+
+impl<'a, R> ReadBufFuture<'a, R> {
+    pub(crate) fn new(reader: R, buf: ReadBufRef<'a>) -> Self {
+        Self { reader, buf }
+    }
+}
+
+impl<R: AsyncRead + Unpin> Future for ReadBufFuture<'_, R> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+        Pin::new(&mut me.reader).poll_read_buf(cx, me.buf.reborrow())
+    }
+}
+
+/// Future for the [`read_buf_exact`](AsyncReadExt::read_buf_exact) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadBufExact<'a, R> {
+    reader: R,
+    buf: ReadBufRef<'a>,
+}
+
+impl<'a, R> ReadBufExact<'a, R> {
+    pub(crate) fn new(reader: R, buf: ReadBufRef<'a>) -> Self {
+        Self { reader, buf }
+    }
+}
+
+impl<R: AsyncRead + Unpin> Future for ReadBufExact<'_, R> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+        while me.buf.remaining() > 0 {
+            let prev_filled = me.buf.filled_len();
+            match ready!(Pin::new(&mut me.reader).poll_read_buf(cx, me.buf.reborrow())) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+
+            if me.buf.filled_len() == prev_filled {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "failed to fill buffer",
+                )));
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Future for the [`read_buf_to_end`](AsyncReadExt::read_buf_to_end) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadBufToEnd<'a, R> {
+    reader: R,
+    buf: &'a mut ReadVec,
+    start: usize,
+    limit: usize,
+}
+
+impl<'a, R> ReadBufToEnd<'a, R> {
+    pub(crate) fn new(reader: R, buf: &'a mut ReadVec, limit: Option<usize>) -> Self {
+        Self {
+            reader,
+            start: buf.filled_len(),
+            buf,
+            limit: limit.unwrap_or(usize::MAX),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> Future for ReadBufToEnd<'_, R> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+        loop {
+            if prepare_to_end(me.buf, me.start, me.limit)? {
+                let mut probe = ReadArray::<1>::new_uninit_array();
+                match ready!(Pin::new(&mut me.reader).poll_read_buf(cx, probe.borrow())) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Poll::Ready(Err(e)),
+                }
+                let read = me.buf.filled_len() - me.start;
+                return Poll::Ready(probe_to_end(probe.filled_len(), read));
+            }
+
+            // read at most one byte past the limit, which is enough to tell that the stream exceeds it
+            let prev_filled = me.buf.filled_len();
+            let max = (me.limit - (prev_filled - me.start)).saturating_add(1);
+            let reader = Pin::new(&mut me.reader);
+            let poll = me
+                .buf
+                .borrow()
+                .with_unfilled_to(max, |buf| reader.poll_read_buf(cx, buf));
+            match ready!(poll) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+
+            if me.buf.filled_len() == prev_filled {
+                return Poll::Ready(Ok(prev_filled - me.start));
+            }
+        }
+    }
+}
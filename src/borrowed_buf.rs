@@ -0,0 +1,83 @@
+//! Integration with [`BorrowedBuf`] and [`BorrowedCursor`] from the standard library
+//!
+//! These are the successors of the `ReadBuf` API that this crate is modelled on. Unlike [`ReadBuf`](crate::ReadBuf),
+//! a [`BorrowedBuf`] only tracks whether its whole buffer is initialized, so partial initialization is lost when
+//! crossing into a [`BorrowedCursor`], but never the other way.
+
+use crate::{ReadBufRef, ReadSlice};
+use core::io::{BorrowedBuf, BorrowedCursor};
+
+impl ReadBufRef<'_> {
+    /// Lends the unfilled region of this buffer to `f` as a [`BorrowedCursor`].
+    ///
+    /// The cursor is only marked as initialized if the whole unfilled region of this buffer is initialized.
+    /// Afterwards, the bytes written to the cursor are added to the filled region, and if the cursor was fully
+    /// initialized, so is this buffer.
+    pub fn with_cursor<T>(&mut self, f: impl FnOnce(BorrowedCursor<'_>) -> T) -> T {
+        self.with_cursor_to(self.remaining(), f)
+    }
+
+    /// Lends the first `n` bytes of the unfilled region of this buffer to `f` as a [`BorrowedCursor`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self.remaining()` is less than `n`.
+    pub(crate) fn with_cursor_to<T>(
+        &mut self,
+        n: usize,
+        f: impl FnOnce(BorrowedCursor<'_>) -> T,
+    ) -> T {
+        let init = self.initialized_len() - self.filled_len() >= n;
+
+        let (res, filled, init) = {
+            // SAFETY: BorrowedBuf never de-initializes bytes
+            let mut borrowed = BorrowedBuf::from(&mut unsafe { self.unfilled_mut() }[..n]);
+            if init {
+                // SAFETY: the first `n` unfilled bytes are initialized
+                unsafe { borrowed.set_init() };
+            }
+
+            let res = f(borrowed.unfilled());
+            (res, borrowed.len(), borrowed.is_init())
+        };
+
+        if init {
+            // SAFETY: BorrowedBuf tracked that the whole buffer has been initialized
+            unsafe { self.assume_init(n) };
+        }
+        // SAFETY: a cursor can only be advanced over initialized bytes
+        unsafe { self.assume_init(filled) };
+        self.add_filled(filled);
+        res
+    }
+}
+
+/// Lends the unfilled region of a [`BorrowedCursor`] to `f` as a [`ReadBufRef`].
+///
+/// This is the reverse of [`ReadBufRef::with_cursor`]. The [`ReadBufRef`] starts empty, and is fully initialized if
+/// the cursor is. Afterwards, the cursor is advanced over the filled bytes, and marked as initialized if the whole
+/// [`ReadBufRef`] has been initialized.
+pub fn with_read_buf<T>(cursor: &mut BorrowedCursor<'_>, f: impl FnOnce(ReadBufRef<'_>) -> T) -> T {
+    let init = cursor.is_init();
+
+    let (res, filled, init) = {
+        // SAFETY: ReadBuf never de-initializes bytes
+        let mut buf = ReadSlice::from(unsafe { cursor.as_mut() });
+        if init {
+            // SAFETY: the whole cursor is initialized
+            unsafe { buf.assume_init(buf.capacity()) };
+        }
+
+        let res = f(buf.borrow());
+        let init = buf.initialized_len() == buf.capacity();
+        (res, buf.filled_len(), init)
+    };
+
+    // SAFETY: ReadBuf tracked that the filled bytes have been initialized
+    unsafe { cursor.advance(filled) };
+    if init {
+        // SAFETY: ReadBuf tracked that the rest of the cursor has been initialized
+        unsafe { cursor.set_init() };
+    }
+    res
+}
@@ -0,0 +1,141 @@
+//! A buffered reader whose buffer storage is generic
+
+use crate::{Bytes, Read, ReadBuf, ReadBufRef, ReadVec};
+use std::{
+    fmt,
+    io::{self, BufRead},
+    mem::MaybeUninit,
+};
+
+pub(crate) const DEFAULT_BUF_SIZE: usize = 8 * 1024;
+
+/// Adds buffering to any reader, like [`io::BufReader`], but the buffer is a [`ReadBuf`] with any [`Bytes`] storage.
+///
+/// This allows the buffer to live on the stack using a [`ReadArray`](crate::ReadArray), or to borrow memory from the
+/// caller using a [`ReadSlice`](crate::ReadSlice). The buffer is filled through [`Read::read_buf`], so its
+/// initialization is tracked and never repeated.
+pub struct BufReader<R, S: Bytes = Box<[MaybeUninit<u8>]>> {
+    buf: ReadBuf<S>,
+    inner: R,
+}
+
+impl<R> BufReader<R> {
+    /// Creates a new `BufReader` with a default buffer capacity, currently 8 KiB.
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    /// Creates a new `BufReader` with the specified buffer capacity.
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        Self::with_buffer(inner, ReadVec::from(Vec::with_capacity(capacity)))
+    }
+}
+
+impl<R, S: Bytes> BufReader<R, S> {
+    /// Creates a new `BufReader` that buffers into `buf`.
+    ///
+    /// Any unconsumed bytes already in `buf` are read before the inner reader.
+    pub fn with_buffer(inner: R, buf: ReadBuf<S>) -> Self {
+        Self { buf, inner }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// It is inadvisable to directly read from the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the internally buffered data.
+    ///
+    /// Unlike [`fill_buf`](BufRead::fill_buf), this will not attempt to fill the buffer if it is empty.
+    pub fn buffer(&self) -> &[u8] {
+        self.buf.unconsumed()
+    }
+
+    /// Returns the number of bytes the internal buffer can hold at once.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Unwraps this `BufReader`, returning the underlying reader.
+    ///
+    /// Note that any leftover data in the internal buffer is lost. Use [`into_parts`](BufReader::into_parts) to
+    /// keep it.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Unwraps this `BufReader`, returning the underlying reader and the buffer.
+    ///
+    /// The unconsumed bytes of the buffer have not been read yet.
+    pub fn into_parts(self) -> (R, ReadBuf<S>) {
+        (self.inner, self.buf)
+    }
+
+    /// Drops the buffered data once it has all been consumed, so the buffer can be filled from the start.
+    fn discard_consumed(&mut self) -> bool {
+        let empty = self.buf.unconsumed().is_empty();
+        if empty {
+            self.buf.clear();
+        }
+        empty
+    }
+}
+
+impl<R: fmt::Debug, S: Bytes> fmt::Debug for BufReader<R, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufReader")
+            .field("reader", &self.inner)
+            .field("buf", &self.buf)
+            .finish()
+    }
+}
+
+impl<R: Read, S: Bytes> io::Read for BufReader<R, S> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        // If we don't have any buffered data and we're doing a massive read
+        // (larger than our internal buffer), bypass our internal buffer entirely.
+        if self.discard_consumed() && out.len() >= self.capacity() {
+            return self.inner.read(out);
+        }
+
+        let n = io::Read::read(&mut self.fill_buf()?, out)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read, S: Bytes> Read for BufReader<R, S> {
+    fn read_buf(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        // If we don't have any buffered data and we're doing a massive read
+        // (larger than our internal buffer), bypass our internal buffer entirely.
+        if self.discard_consumed() && buf.remaining() >= self.capacity() {
+            return Read::read_buf(&mut self.inner, buf);
+        }
+
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.remaining());
+        buf.append(&available[..n]);
+        self.consume(n);
+        Ok(())
+    }
+}
+
+impl<R: Read, S: Bytes> BufRead for BufReader<R, S> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.discard_consumed() {
+            Read::read_buf(&mut self.inner, self.buf.borrow())?;
+        }
+        Ok(self.buf.unconsumed())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.buf.consume(amt.min(self.buf.unconsumed().len()))
+    }
+}
@@ -0,0 +1,169 @@
+//! A buffered writer whose buffer storage is generic
+
+use crate::{buf_reader::DEFAULT_BUF_SIZE, Bytes, ReadBuf, ReadVec};
+use std::{
+    fmt,
+    io::{self, Write},
+    mem::{ManuallyDrop, MaybeUninit},
+    ptr,
+};
+
+/// Adds buffering to any writer, like [`io::BufWriter`], but the buffer is a [`ReadBuf`] with any [`Bytes`] storage.
+///
+/// Pending bytes are kept in the filled region of the buffer. Bytes that have been written to the inner writer by a
+/// partial flush are marked as consumed, so a short write never loses or repeats data.
+///
+/// Like [`io::BufWriter`], the buffer is flushed when the writer is dropped, and any errors are ignored. Call
+/// [`flush`](Write::flush) before dropping to handle them.
+pub struct BufWriter<W: Write, S: Bytes = Box<[MaybeUninit<u8>]>> {
+    buf: ReadBuf<S>,
+    inner: W,
+    // #30888: If the inner writer panics in a call to write, we don't want to
+    // write the buffered data a second time in BufWriter's destructor. This
+    // flag tells the Drop impl if it should skip the flush.
+    panicked: bool,
+}
+
+impl<W: Write> BufWriter<W> {
+    /// Creates a new `BufWriter` with a default buffer capacity, currently 8 KiB.
+    pub fn new(inner: W) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    /// Creates a new `BufWriter` with the specified buffer capacity.
+    pub fn with_capacity(capacity: usize, inner: W) -> Self {
+        Self::with_buffer(inner, ReadVec::from(Vec::with_capacity(capacity)))
+    }
+}
+
+impl<W: Write, S: Bytes> BufWriter<W, S> {
+    /// Creates a new `BufWriter` that buffers into `buf`.
+    ///
+    /// Any unconsumed bytes already in `buf` are written before any new data.
+    pub fn with_buffer(inner: W, buf: ReadBuf<S>) -> Self {
+        Self {
+            buf,
+            inner,
+            panicked: false,
+        }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// It is inadvisable to directly write to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the internally buffered data that has not been written yet.
+    pub fn buffer(&self) -> &[u8] {
+        self.buf.unconsumed()
+    }
+
+    /// Returns the number of bytes the internal buffer can hold without flushing.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Writes the buffered data to the inner writer.
+    ///
+    /// If the inner writer only accepts part of the data, the rest is kept at the start of the buffer.
+    fn flush_buf(&mut self) -> io::Result<()> {
+        let res = loop {
+            let pending = self.buf.unconsumed();
+            if pending.is_empty() {
+                break Ok(());
+            }
+
+            self.panicked = true;
+            let r = self.inner.write(pending);
+            self.panicked = false;
+
+            match r {
+                Ok(0) => {
+                    break Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    ))
+                }
+                Ok(n) => self.buf.consume(n),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+
+        self.buf.compact();
+        res
+    }
+
+    /// Unwraps this `BufWriter`, returning the underlying writer.
+    ///
+    /// The buffer is written out before returning the writer.
+    ///
+    /// # Errors
+    ///
+    /// If an error occurs while flushing the buffer, it is returned along with the `BufWriter`, so no data is lost.
+    pub fn into_inner(mut self) -> Result<W, (io::Error, Self)> {
+        match self.flush_buf() {
+            Ok(()) => Ok(self.into_parts().0),
+            Err(e) => Err((e, self)),
+        }
+    }
+
+    /// Unwraps this `BufWriter`, returning the underlying writer and the buffer.
+    ///
+    /// The unconsumed bytes of the buffer have not been written yet.
+    pub fn into_parts(self) -> (W, ReadBuf<S>) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never dropped, so each field is only moved out once
+        unsafe { (ptr::read(&this.inner), ptr::read(&this.buf)) }
+    }
+}
+
+impl<W: Write + fmt::Debug, S: Bytes> fmt::Debug for BufWriter<W, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufWriter")
+            .field("writer", &self.inner)
+            .field("buf", &self.buf)
+            .finish()
+    }
+}
+
+impl<W: Write, S: Bytes> Write for BufWriter<W, S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.buf.remaining() {
+            self.flush_buf()?;
+        }
+
+        // If the data doesn't fit in our internal buffer,
+        // bypass it and write directly to the inner writer.
+        if buf.len() > self.buf.remaining() {
+            self.panicked = true;
+            let r = self.inner.write(buf);
+            self.panicked = false;
+            r
+        } else {
+            self.buf.append(buf);
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write, S: Bytes> Drop for BufWriter<W, S> {
+    fn drop(&mut self) {
+        if !self.panicked {
+            // dtors should not panic, so we ignore a failed flush
+            let _r = self.flush_buf();
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Integration with the [`bytes`] crate
+
+use crate::{Bytes, ReadBuf, ReadBufRef};
+use bytes::{buf::UninitSlice, Buf, BufMut, BytesMut};
+use cl_generic_vec::raw::{AllocError, AllocResult, Storage};
+use core::{mem::MaybeUninit, ptr::NonNull, slice};
+
+/// A [`Storage`] over the capacity of a [`BytesMut`].
+///
+/// The storage has a fixed capacity, like a slice. Reserve space in the [`BytesMut`] before converting it into a
+/// [`ReadBytesMut`].
+pub struct BytesStorage {
+    bytes: BytesMut,
+    ptr: NonNull<MaybeUninit<u8>>,
+    capacity: usize,
+}
+
+// SAFETY: `ptr` points into the buffer owned by `bytes`, which is `Send` and `Sync`
+unsafe impl Send for BytesStorage {}
+unsafe impl Sync for BytesStorage {}
+
+/// A [`ReadBuf`] that owns it's buffer using a [`BytesMut`]
+pub type ReadBytesMut = ReadBuf<BytesStorage>;
+
+// The length of the inner `BytesMut` is kept at 0, so the whole buffer is its spare capacity, and the initialized
+// length is tracked by the `init` counter of the `ReadBuf` instead. The pointer to the buffer is taken from
+// `spare_capacity_mut` once, since the pointer behind `Deref` only covers the (empty) initialized part.
+impl BytesStorage {
+    fn new(mut bytes: BytesMut) -> Self {
+        // SAFETY: shrinking the length never exposes uninitialized bytes
+        unsafe { bytes.set_len(0) };
+        let spare = bytes.spare_capacity_mut();
+        let capacity = spare.len();
+        let ptr = NonNull::from(spare).cast();
+        Self {
+            bytes,
+            ptr,
+            capacity,
+        }
+    }
+}
+
+impl AsRef<[MaybeUninit<u8>]> for BytesStorage {
+    fn as_ref(&self) -> &[MaybeUninit<u8>] {
+        // SAFETY: `ptr` is valid for `capacity` bytes of the buffer owned by `bytes`
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.capacity) }
+    }
+}
+
+impl AsMut<[MaybeUninit<u8>]> for BytesStorage {
+    fn as_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        // SAFETY: `ptr` is valid for `capacity` bytes of the buffer owned by `bytes`, and `&mut self` is unique
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.capacity) }
+    }
+}
+
+unsafe impl Storage for BytesStorage {
+    type Item = u8;
+
+    fn reserve(&mut self, new_capacity: usize) {
+        if new_capacity > self.capacity {
+            panic!(
+                "Tried to reserve {}, but used a fixed capacity storage of {}",
+                new_capacity, self.capacity
+            )
+        }
+    }
+
+    fn try_reserve(&mut self, new_capacity: usize) -> AllocResult {
+        if new_capacity <= self.capacity {
+            Ok(())
+        } else {
+            Err(AllocError)
+        }
+    }
+}
+
+/// Create a [`ReadBuf`] from a partially initialised [`BytesMut`].
+/// Will begin with 0 filled bytes.
+impl From<BytesMut> for ReadBytesMut {
+    fn from(buf: BytesMut) -> Self {
+        let init = buf.len();
+        ReadBuf {
+            consumed: 0,
+            filled: 0,
+            init,
+            window: 0,
+            buf: BytesStorage::new(buf),
+        }
+    }
+}
+
+/// Extract the filled bytes of a [`ReadBuf`] into a [`BytesMut`], keeping the allocation.
+///
+/// Like the conversion of a [`ReadVec`](crate::ReadVec) into a `Vec<u8>`, the result has the filled length, so bytes
+/// that were already consumed are kept. [`advance`](Buf::advance) it by the consumed length to skip them. The result
+/// can be [`freeze`](BytesMut::freeze)d into a [`Bytes`](bytes::Bytes) without copying.
+impl From<ReadBytesMut> for BytesMut {
+    fn from(buf: ReadBytesMut) -> Self {
+        let mut bytes = buf.buf.bytes;
+        // SAFETY: the filled bytes are initialized
+        unsafe { bytes.set_len(buf.filled) };
+        bytes
+    }
+}
+
+/// Writes into the unfilled region of the buffer, without initializing it first.
+///
+/// The buffer never grows, so [`remaining_mut`](BufMut::remaining_mut) is the number of unfilled bytes, even for a
+/// [`ReadVec`](crate::ReadVec).
+unsafe impl<S: Bytes> BufMut for ReadBuf<S> {
+    #[inline]
+    fn remaining_mut(&self) -> usize {
+        self.remaining()
+    }
+
+    #[inline]
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        self.assume_init(cnt);
+        self.add_filled(cnt);
+    }
+
+    #[inline]
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        // SAFETY: UninitSlice can only be written to, so it never de-initializes bytes
+        UninitSlice::uninit(unsafe { self.unfilled_mut() })
+    }
+
+    #[inline]
+    fn put_slice(&mut self, src: &[u8]) {
+        self.append(src);
+    }
+}
+
+/// Writes into the unfilled region of the buffer, without initializing it first.
+///
+/// See the implementation for [`ReadBuf`].
+unsafe impl BufMut for ReadBufRef<'_> {
+    #[inline]
+    fn remaining_mut(&self) -> usize {
+        self.remaining()
+    }
+
+    #[inline]
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        self.assume_init(cnt);
+        self.add_filled(cnt);
+    }
+
+    #[inline]
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        // SAFETY: UninitSlice can only be written to, so it never de-initializes bytes
+        UninitSlice::uninit(unsafe { self.unfilled_mut() })
+    }
+
+    #[inline]
+    fn put_slice(&mut self, src: &[u8]) {
+        self.append(src);
+    }
+}
+
+/// Reads the unconsumed part of the filled region, marking bytes as consumed as they are read.
+impl<S: Bytes> Buf for ReadBuf<S> {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.unconsumed().len()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.unconsumed()
+    }
+
+    #[inline]
+    fn advance(&mut self, cnt: usize) {
+        self.consume(cnt);
+    }
+}
@@ -0,0 +1,120 @@
+//! Errors returned by the fallible methods of [`ReadBuf`](crate::ReadBuf)
+
+use core::fmt;
+
+/// The error returned when a buffer does not have enough unfilled capacity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError {
+    requested: usize,
+    available: usize,
+}
+
+impl CapacityError {
+    pub(crate) fn new(requested: usize, available: usize) -> Self {
+        Self {
+            requested,
+            available,
+        }
+    }
+
+    /// Returns the number of unfilled bytes that were requested
+    pub fn requested(&self) -> usize {
+        self.requested
+    }
+
+    /// Returns the number of unfilled bytes that were available
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "requested {} bytes but only {} bytes of capacity remain",
+            self.requested, self.available
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityError {}
+
+/// The error returned when the filled region of a buffer would become larger than the initialized region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitError {
+    requested: usize,
+    available: usize,
+}
+
+impl InitError {
+    pub(crate) fn new(requested: usize, available: usize) -> Self {
+        Self {
+            requested,
+            available,
+        }
+    }
+
+    /// Returns the size of the filled region that was requested
+    pub fn requested(&self) -> usize {
+        self.requested
+    }
+
+    /// Returns the number of bytes that are initialized
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "requested {} filled bytes but only {} bytes are initialized",
+            self.requested, self.available
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InitError {}
+
+/// The error returned when more bytes are consumed than have been filled and not yet consumed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsumeError {
+    requested: usize,
+    available: usize,
+}
+
+impl ConsumeError {
+    pub(crate) fn new(requested: usize, available: usize) -> Self {
+        Self {
+            requested,
+            available,
+        }
+    }
+
+    /// Returns the number of bytes that were requested to be consumed
+    pub fn requested(&self) -> usize {
+        self.requested
+    }
+
+    /// Returns the number of unconsumed bytes that were available
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
+
+impl fmt::Display for ConsumeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "requested to consume {} bytes but only {} bytes are unconsumed",
+            self.requested, self.available
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConsumeError {}
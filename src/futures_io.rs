@@ -0,0 +1,107 @@
+//! Integration with [`futures_io::AsyncRead`]
+
+use crate::{
+    read::{grow_window, window_len},
+    ReadBufExact, ReadBufFuture, ReadBufRef, ReadBufToEnd, ReadSlice, ReadVec,
+};
+use futures_io::AsyncRead;
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Pull some bytes from `reader` into `buf`.
+///
+/// [`AsyncRead`] can only read into initialized memory, so like [`Read::read_buf`](crate::Read::read_buf), a window
+/// at the start of the unfilled region is initialized first. The window doubles after each read that fills it.
+pub fn poll_read_buf<R: AsyncRead + ?Sized>(
+    reader: Pin<&mut R>,
+    cx: &mut Context<'_>,
+    mut buf: ReadBufRef<'_>,
+) -> Poll<io::Result<()>> {
+    let window = window_len(&buf);
+    let n = ready!(reader.poll_read(cx, buf.initialize_unfilled_to(window)))?;
+    buf.add_filled(n);
+    grow_window(&mut buf, window, n);
+    Poll::Ready(Ok(()))
+}
+
+/// Marks a [`Compat`] adapter for [`AsyncRead`].
+#[derive(Debug)]
+pub enum FuturesIo {}
+
+/// An adapter between [`AsyncRead`] and this crate's [`AsyncRead`](crate::AsyncRead).
+///
+/// `Compat<R>` implements [`crate::AsyncRead`] when `R` implements [`AsyncRead`], reading through
+/// [`poll_read_buf`]. In the other direction, it implements [`AsyncRead`] when `R` implements
+/// [`crate::AsyncRead`], reading through a [`ReadSlice`] over the caller's buffer.
+pub type Compat<R> = crate::Compat<R, FuturesIo>;
+
+impl<R: AsyncRead> crate::AsyncRead for Compat<R> {
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufRef<'_>,
+    ) -> Poll<io::Result<()>> {
+        poll_read_buf(self.project(), cx, buf)
+    }
+}
+
+impl<R: crate::AsyncRead> AsyncRead for Compat<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut buf = ReadSlice::from(buf);
+        ready!(self.project().poll_read_buf(cx, buf.borrow()))?;
+        Poll::Ready(Ok(buf.filled_len()))
+    }
+}
+
+/// An extension of [`AsyncRead`] that can read into [`ReadBuf`](crate::ReadBuf)s.
+///
+/// The returned futures are cancellation safe: bytes that have been read are added to the filled region of the
+/// buffer straight away, so they stay there if the future is dropped before completing. They are shared with
+/// [`AsyncReadExt`](crate::AsyncReadExt), reading through a [`Compat`] adapter.
+pub trait AsyncReadBufExt: AsyncRead {
+    /// Pull some bytes from this source into the specified buffer.
+    ///
+    /// This is the async equivalent of [`Read::read_buf`](crate::Read::read_buf).
+    fn read_buf<'a>(&'a mut self, buf: ReadBufRef<'a>) -> ReadBufFuture<'a, Compat<&'a mut Self>>
+    where
+        Self: Unpin,
+    {
+        ReadBufFuture::new(Compat::new(self), buf)
+    }
+
+    /// Read the exact number of bytes required to fill `buf`.
+    ///
+    /// This is the async equivalent of [`Read::read_buf_exact`](crate::Read::read_buf_exact).
+    fn read_buf_exact<'a>(
+        &'a mut self,
+        buf: ReadBufRef<'a>,
+    ) -> ReadBufExact<'a, Compat<&'a mut Self>>
+    where
+        Self: Unpin,
+    {
+        ReadBufExact::new(Compat::new(self), buf)
+    }
+
+    /// Read all bytes until EOF in this source, appending them to `buf`.
+    ///
+    /// This is the async equivalent of [`Read::read_buf_to_end`](crate::Read::read_buf_to_end).
+    fn read_buf_to_end<'a>(
+        &'a mut self,
+        buf: &'a mut ReadVec,
+        limit: Option<usize>,
+    ) -> ReadBufToEnd<'a, Compat<&'a mut Self>>
+    where
+        Self: Unpin,
+    {
+        ReadBufToEnd::new(Compat::new(self), buf, limit)
+    }
+}
+
+impl<R: AsyncRead + ?Sized> AsyncReadBufExt for R {}
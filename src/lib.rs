@@ -1,60 +1,77 @@
-//! ReadBuf types and methods. See https://github.com/rust-lang/rust/issues/78485
-
-use cl_generic_vec::{raw::Storage, ArrayVec, HeapVec, SimpleVec, SliceVec};
-use std::{cmp, fmt, io, mem::MaybeUninit, ops::Deref};
+//! ReadBuf types and methods. See <https://github.com/rust-lang/rust/issues/78485>
+//!
+//! The buffer types only depend on `core`. `ReadVec` additionally requires the `alloc` feature, and the `Read`
+//! trait requires the `std` feature. Both are enabled by default.
+//!
+//! Building without the `std` feature does not work yet: `cl-generic-vec` 0.4.0, the latest release of the
+//! dependency, fails to compile without its own `std` feature. Until a fixed release is available, `no_std` builds
+//! need a `[patch.crates-io]` entry pointing at a fixed copy of `cl-generic-vec`.
+//!
+//! `AsyncRead` is a runtime-agnostic async version of `Read`, also requiring the `std` feature. The `tokio` and
+//! `futures-io` features add reads from each crate's `AsyncRead` types, and adapters to and from `AsyncRead`, in
+//! the `tokio` and `futures_io` modules.
+//!
+//! The `bytes` feature implements `bytes::BufMut` for [`ReadBuf`] and [`ReadBufRef`], writing into the unfilled
+//! region without initializing it, and `bytes::Buf` for [`ReadBuf`], reading from the unconsumed bytes. The `bytes`
+//! module also provides a [`ReadBuf`] backed by a `BytesMut`, which can be frozen without copying.
+//!
+//! The `nightly` feature requires a nightly compiler. It adds conversions to and from the standard library's
+//! `BorrowedBuf` and `BorrowedCursor` in the `borrowed_buf` module, and makes the default `Read::read_buf` call
+//! `std::io::Read::read_buf`, so the uninitialized reads of standard library types are used.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(
+    feature = "nightly",
+    feature(core_io, core_io_borrowed_buf, borrowed_buf_init, read_buf)
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod async_read;
+#[cfg(feature = "nightly")]
+pub mod borrowed_buf;
+#[cfg(feature = "std")]
+mod buf_reader;
+#[cfg(feature = "std")]
+mod buf_writer;
+#[cfg(feature = "bytes")]
+pub mod bytes;
+mod error;
+#[cfg(feature = "futures-io")]
+pub mod futures_io;
+#[cfg(feature = "std")]
+mod read;
+#[cfg(feature = "std")]
+pub use async_read::{AsyncRead, AsyncReadExt, Compat, ReadBufExact, ReadBufFuture, ReadBufToEnd};
+#[cfg(feature = "std")]
+pub use buf_reader::BufReader;
+#[cfg(feature = "std")]
+pub use buf_writer::BufWriter;
+pub use error::{CapacityError, ConsumeError, InitError};
+#[cfg(feature = "std")]
+pub use read::{InvalidUtf8, Read, Reader, TrustedRead, TrustedReader};
+
+mod ring;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(feature = "alloc")]
+pub use ring::RingVec;
+pub use ring::{RingArray, RingBuf, RingSlice};
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, collections::TryReserveError, vec::Vec};
+#[cfg(feature = "alloc")]
+use cl_generic_vec::HeapVec;
+use cl_generic_vec::{raw::Storage, SimpleVec, SliceVec};
+#[cfg(feature = "alloc")]
+use core::mem;
+use core::{cmp, fmt, mem::MaybeUninit, ptr};
 
 /// A [`Storage`] of [`u8`]s
 pub trait Bytes: Storage<Item = u8> {}
 impl<S: Storage<Item = u8>> Bytes for S {}
 
-/// A wrapper over [`io::Read`] to provide the custom read_buf* methods on stable
-pub trait Read: io::Read {
-    /// Pull some bytes from this source into the specified buffer.
-    ///
-    /// This is equivalent to the [`read`](io::Read::read) method, except that it is passed a [`ReadBufRef`] rather than `[u8]` to allow use
-    /// with uninitialized buffers. The new data will be appended to any existing contents of `buf`.
-    ///
-    /// The default implementation delegates to `read`.
-    fn read_buf(&mut self, buf: ReadBufRef<'_, impl Bytes>) -> io::Result<()> {
-        default_read_buf(|b| self.read(b), buf)
-    }
-
-    /// Read the exact number of bytes required to fill `buf`.
-    ///
-    /// This is equivalent to the [`read_exact`](io::Read::read_exact) method, except that it is passed a [`ReadBufRef`] rather than `[u8]` to
-    /// allow use with uninitialized buffers.
-    fn read_buf_exact(&mut self, mut buf: ReadBufRef<'_, impl Bytes>) -> io::Result<()> {
-        while buf.remaining() > 0 {
-            let prev_filled = buf.filled().len();
-            match Read::read_buf(self, buf.reborrow()) {
-                Ok(()) => {}
-                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
-                Err(e) => return Err(e),
-            }
-
-            if buf.filled().len() == prev_filled {
-                return Err(io::Error::new(
-                    io::ErrorKind::UnexpectedEof,
-                    "failed to fill buffer",
-                ));
-            }
-        }
-
-        Ok(())
-    }
-}
-
-impl<R: io::Read> Read for R {}
-
-pub(crate) fn default_read_buf<F>(read: F, mut buf: ReadBufRef<'_, impl Bytes>) -> io::Result<()>
-where
-    F: FnOnce(&mut [u8]) -> io::Result<usize>,
-{
-    let n = read(buf.initialize_unfilled())?;
-    buf.add_filled(n);
-    Ok(())
-}
-
 /// A wrapper around a byte buffer that is incrementally filled and initialized.
 ///
 /// This type is a sort of "double cursor". It tracks three regions in the buffer: a region at the beginning of the
@@ -62,23 +79,35 @@ where
 /// logically filled, and a region at the end that is fully uninitialized. The filled region is guaranteed to be a
 /// subset of the initialized region.
 ///
+/// The filled region additionally tracks how much of it has been consumed, so the buffer can be read from as well
+/// as written to. The consumed region is guaranteed to be a subset of the filled region.
+///
 /// In summary, the contents of the buffer can be visualized as:
 /// ```not_rust
-/// [             capacity              ]
-/// [ filled |         unfilled         ]
-/// [    initialized    | uninitialized ]
+/// [                 capacity                  ]
+/// [ consumed | unconsumed |      unfilled     ]
+/// [         filled        |      unfilled     ]
+/// [       initialized        | uninitialized  ]
 /// ```
+///
+/// Readers are passed a [`ReadBufRef`], which erases the storage type, so they only need to be written once for
+/// every kind of `ReadBuf`.
 pub struct ReadBuf<S: Bytes> {
+    consumed: usize,
     filled: usize,
-    buf: SimpleVec<S>,
+    init: usize,
+    // the size of the next window that the default `Read::read_buf` reads into
+    window: usize,
+    buf: S,
 }
 
 impl<S: Bytes> fmt::Debug for ReadBuf<S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ReadBuf")
-            .field("init", &self.buf.len())
+            .field("init", &self.init)
             .field("filled", &self.filled)
-            .field("capacity", &self.buf.capacity())
+            .field("consumed", &self.consumed)
+            .field("capacity", &self.capacity())
             .finish()
     }
 }
@@ -86,6 +115,7 @@ impl<S: Bytes> fmt::Debug for ReadBuf<S> {
 /// A [`ReadBuf`] that takes it's buffer from an existing slice
 pub type ReadSlice<'a> = ReadBuf<&'a mut [MaybeUninit<u8>]>;
 /// A [`ReadBuf`] that owns it's buffer using a [`Vec<u8>`]
+#[cfg(feature = "alloc")]
 pub type ReadVec = ReadBuf<Box<[MaybeUninit<u8>]>>;
 /// A [`ReadBuf`] that owns it's buffer using a [`[MaybeUninit<u8>; N]`](array)
 pub type ReadArray<const N: usize> = ReadBuf<[MaybeUninit<u8>; N]>;
@@ -95,8 +125,11 @@ impl<const N: usize> ReadArray<N> {
     /// Will begin with 0 filled bytes.
     pub fn new_uninit_array() -> Self {
         Self {
+            consumed: 0,
             filled: 0,
-            buf: ArrayVec::new(),
+            init: 0,
+            window: 0,
+            buf: [MaybeUninit::uninit(); N],
         }
     }
 }
@@ -106,30 +139,42 @@ impl<const N: usize> ReadArray<N> {
 impl<const N: usize> From<[u8; N]> for ReadArray<N> {
     fn from(buf: [u8; N]) -> Self {
         ReadBuf {
+            consumed: 0,
             filled: 0,
-            buf: ArrayVec::from_array(buf),
+            init: N,
+            window: 0,
+            buf: buf.map(MaybeUninit::new),
         }
     }
 }
 
 /// Create a [`ReadBuf`] from a partially initialised vec of bytes.
 /// Will begin with 0 filled bytes.
+#[cfg(feature = "alloc")]
 impl From<Vec<u8>> for ReadVec {
     fn from(buf: Vec<u8>) -> Self {
+        let (init, buf) = HeapVec::from(buf).into_raw_parts();
         ReadBuf {
+            consumed: 0,
             filled: 0,
-            buf: buf.into(),
+            init,
+            window: 0,
+            buf,
         }
     }
 }
 
 /// Create a [`ReadBuf`] from an uninitialised boxed-slice of bytes.
 /// Will begin with 0 filled bytes.
+#[cfg(feature = "alloc")]
 impl From<Box<[MaybeUninit<u8>]>> for ReadVec {
     fn from(buf: Box<[MaybeUninit<u8>]>) -> Self {
         ReadBuf {
+            consumed: 0,
             filled: 0,
-            buf: HeapVec::with_storage(buf),
+            init: 0,
+            window: 0,
+            buf,
         }
     }
 }
@@ -138,9 +183,13 @@ impl From<Box<[MaybeUninit<u8>]>> for ReadVec {
 /// Will begin with 0 filled bytes.
 impl<'a> From<&'a mut [u8]> for ReadSlice<'a> {
     fn from(buf: &'a mut [u8]) -> Self {
+        let (init, buf) = SliceVec::full(buf).into_raw_parts();
         ReadBuf {
+            consumed: 0,
             filled: 0,
-            buf: SliceVec::full(buf),
+            init,
+            window: 0,
+            buf,
         }
     }
 }
@@ -150,41 +199,146 @@ impl<'a> From<&'a mut [u8]> for ReadSlice<'a> {
 impl<'a> From<&'a mut [MaybeUninit<u8>]> for ReadSlice<'a> {
     fn from(buf: &'a mut [MaybeUninit<u8>]) -> Self {
         ReadBuf {
+            consumed: 0,
             filled: 0,
-            buf: unsafe { SliceVec::new(buf) },
+            init: 0,
+            window: 0,
+            buf,
         }
     }
 }
 
+/// Extract the filled bytes of a [`ReadBuf`] into a vec, keeping the allocation.
+///
+/// The vec has the filled length, so bytes that were already consumed are kept at its start. The `bytes` feature's
+/// conversion into a `BytesMut` does the same.
+#[cfg(feature = "alloc")]
+impl From<ReadVec> for Vec<u8> {
+    fn from(buf: ReadVec) -> Self {
+        let filled = buf.filled;
+        let mut vec: Vec<u8> = buf.into_vec().into();
+        vec.truncate(filled);
+        vec
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ReadVec {
+    /// Reserves capacity for at least `additional` more bytes to be filled.
+    ///
+    /// The filled and initialized regions are preserved across the reallocation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    pub fn reserve(&mut self, additional: usize) {
+        let additional = self.additional_init(additional);
+        self.with_vec(|vec| vec.reserve(additional))
+    }
+
+    /// Reserves capacity for exactly `additional` more bytes to be filled.
+    ///
+    /// The filled and initialized regions are preserved across the reallocation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let additional = self.additional_init(additional);
+        self.with_vec(|vec| vec.reserve_exact(additional))
+    }
+
+    /// Tries to reserve capacity for at least `additional` more bytes to be filled.
+    ///
+    /// The filled and initialized regions are preserved across the reallocation.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an error is returned
+    /// and the buffer is left unchanged.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let additional = self.additional_init(additional);
+        self.with_vec(|vec| vec.try_reserve(additional))
+    }
+
+    /// Tries to reserve capacity for exactly `additional` more bytes to be filled.
+    ///
+    /// The filled and initialized regions are preserved across the reallocation.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an error is returned
+    /// and the buffer is left unchanged.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let additional = self.additional_init(additional);
+        self.with_vec(|vec| vec.try_reserve_exact(additional))
+    }
+
+    /// Converts a request for `additional` unfilled bytes into additional bytes past the initialized region
+    fn additional_init(&self, additional: usize) -> usize {
+        self.filled
+            .saturating_add(additional)
+            .saturating_sub(self.init)
+    }
+
+    fn with_vec<T>(&mut self, f: impl FnOnce(&mut Vec<u8>) -> T) -> T {
+        // reset the cursors while the storage is taken so a panic can't leave them past the initialized region
+        let consumed = mem::replace(&mut self.consumed, 0);
+        let filled = mem::replace(&mut self.filled, 0);
+        let init = mem::replace(&mut self.init, 0);
+        let buf = mem::replace(&mut self.buf, Box::new([]));
+        // SAFETY: the first `init` bytes of the storage are initialized
+        let mut vec: Vec<u8> = unsafe { HeapVec::from_raw_parts(init, buf) }.into();
+        let res = f(&mut vec);
+        (self.init, self.buf) = HeapVec::from(vec).into_raw_parts();
+        self.filled = filled;
+        self.consumed = consumed;
+        res
+    }
+}
+
 impl<S: Bytes> ReadBuf<S> {
     /// Extract the bytes from the [`ReadBuf`]
     pub fn into_inner(self) -> SimpleVec<S> {
-        assert_eq!(self.filled, self.buf.len());
-        self.buf
+        assert_eq!(self.filled, self.init);
+        self.into_vec()
+    }
+
+    /// Returns the storage as a vec of the initialized bytes.
+    pub(crate) fn into_vec(self) -> SimpleVec<S> {
+        // SAFETY: the first `init` bytes of the storage are initialized
+        unsafe { SimpleVec::from_raw_parts(self.init, self.buf) }
     }
 
     /// Creates a new [`ReadBufRef`] referencing this `ReadBuf`.
     #[inline]
-    pub fn borrow(&mut self) -> ReadBufRef<'_, S> {
-        ReadBufRef { read_buf: self }
+    pub fn borrow(&mut self) -> ReadBufRef<'_> {
+        ReadBufRef {
+            buf: self.buf.as_mut(),
+            consumed: &mut self.consumed,
+            filled: &mut self.filled,
+            init: &mut self.init,
+            window: &mut self.window,
+        }
     }
 
     /// Returns the total capacity of the buffer.
     #[inline]
     pub fn capacity(&self) -> usize {
-        self.buf.capacity()
+        self.buf.as_ref().len()
     }
 
     /// Returns a shared reference to the filled portion of the buffer.
     #[inline]
     pub fn filled(&self) -> &[u8] {
-        &self.buf[..self.filled]
+        &self.initialized()[..self.filled]
     }
 
     /// Returns a mutable reference to the filled portion of the buffer.
     #[inline]
     pub fn filled_mut(&mut self) -> &mut [u8] {
-        &mut self.buf[..self.filled]
+        let filled = self.filled;
+        &mut self.initialized_mut()[..filled]
     }
 
     /// Returns a shared reference to the initialized portion of the buffer.
@@ -192,7 +346,8 @@ impl<S: Bytes> ReadBuf<S> {
     /// This includes the filled portion.
     #[inline]
     pub fn initialized(&self) -> &[u8] {
-        self.buf.as_slice()
+        // SAFETY: the first `init` bytes of the storage are initialized
+        unsafe { slice_assume_init(&self.buf.as_ref()[..self.init]) }
     }
 
     /// Returns a mutable reference to the initialized portion of the buffer.
@@ -200,7 +355,8 @@ impl<S: Bytes> ReadBuf<S> {
     /// This includes the filled portion.
     #[inline]
     pub fn initialized_mut(&mut self) -> &mut [u8] {
-        self.buf.as_mut_slice()
+        // SAFETY: the first `init` bytes of the storage are initialized
+        unsafe { slice_assume_init_mut(&mut self.buf.as_mut()[..self.init]) }
     }
 
     /// Returns a mutable reference to the unfilled part of the buffer without ensuring that it has been fully
@@ -209,9 +365,13 @@ impl<S: Bytes> ReadBuf<S> {
     /// # Safety
     ///
     /// The caller must not de-initialize portions of the buffer that have already been initialized.
+    ///
+    /// Writing bytes never de-initializes them, so the slice can be handed to anything that only writes to it. A
+    /// `std::io::Read` that never reads from its buffer can implement `TrustedRead` to be given this slice by
+    /// `TrustedReader`, instead of a zeroed one.
     #[inline]
     pub unsafe fn unfilled_mut(&mut self) -> &mut [MaybeUninit<u8>] {
-        &mut self.buf.storage_mut().as_mut()[self.filled..]
+        &mut self.buf.as_mut()[self.filled..]
     }
 
     /// Returns a mutable reference to the uninitialized part of the buffer.
@@ -219,7 +379,7 @@ impl<S: Bytes> ReadBuf<S> {
     /// It is safe to uninitialize any of these bytes.
     #[inline]
     pub fn uninitialized_mut(&mut self) -> &mut [MaybeUninit<u8>] {
-        self.buf.spare_capacity_mut()
+        &mut self.buf.as_mut()[self.init..]
     }
 
     /// Returns a mutable reference to the unfilled part of the buffer, ensuring it is fully initialized.
@@ -228,7 +388,7 @@ impl<S: Bytes> ReadBuf<S> {
     /// the first use.
     #[inline]
     pub fn initialize_unfilled(&mut self) -> &mut [u8] {
-        // should optimize out the assertion
+        // the capacity check in `try_initialize_unfilled_to` always passes, so this never panics
         self.initialize_unfilled_to(self.remaining())
     }
 
@@ -237,30 +397,28 @@ impl<S: Bytes> ReadBuf<S> {
     ///
     /// # Panics
     ///
-    /// Panics if `self.remaining()` is less than `n`.
+    /// Panics if `self.remaining()` is less than `n`. See
+    /// [`try_initialize_unfilled_to`](ReadBuf::try_initialize_unfilled_to) for a fallible version.
     #[inline]
+    #[track_caller]
     pub fn initialize_unfilled_to(&mut self, n: usize) -> &mut [u8] {
-        assert!(self.remaining() >= n);
-
-        let extra_init = self.buf.len() - self.filled;
-        // If we don't have enough initialized, do zeroing
-        if n > extra_init {
-            let uninit = n - extra_init;
-            let unfilled = &mut self.uninitialized_mut()[0..uninit];
-
-            for byte in unfilled.iter_mut() {
-                byte.write(0);
-            }
-
-            // SAFETY: we just initialized uninit bytes, and the previous bytes were already init
-            unsafe {
-                self.assume_init(n);
-            }
+        match self.try_initialize_unfilled_to(n) {
+            Ok(unfilled) => unfilled,
+            Err(e) => panic!("{e}"),
         }
+    }
 
+    /// Returns a mutable reference to the first `n` bytes of the unfilled part of the buffer, ensuring it is
+    /// fully initialized.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without initializing anything, if `self.remaining()` is less than `n`.
+    #[inline]
+    pub fn try_initialize_unfilled_to(&mut self, n: usize) -> Result<&mut [u8], CapacityError> {
+        self.borrow().try_initialize_unfilled_to(n)?;
         let filled = self.filled;
-
-        &mut self.initialized_mut()[filled..filled + n]
+        Ok(&mut self.initialized_mut()[filled..filled + n])
     }
 
     /// Returns the number of bytes at the end of the slice that have not yet been filled.
@@ -269,12 +427,12 @@ impl<S: Bytes> ReadBuf<S> {
         self.capacity() - self.filled
     }
 
-    /// Clears the buffer, resetting the filled region to empty.
+    /// Clears the buffer, resetting the filled and consumed regions to empty.
     ///
     /// The number of initialized bytes is not changed, and the contents of the buffer are not modified.
     #[inline]
     pub fn clear(&mut self) {
-        self.set_filled(0); // The assertion in `set_filled` is optimized out
+        self.borrow().clear()
     }
 
     /// Increases the size of the filled region of the buffer.
@@ -283,10 +441,25 @@ impl<S: Bytes> ReadBuf<S> {
     ///
     /// # Panics
     ///
-    /// Panics if the filled region of the buffer would become larger than the initialized region.
+    /// Panics if the filled region of the buffer would become larger than the initialized region. See
+    /// [`try_add_filled`](ReadBuf::try_add_filled) for a fallible version.
     #[inline]
+    #[track_caller]
     pub fn add_filled(&mut self, n: usize) {
-        self.set_filled(self.filled + n);
+        self.borrow().add_filled(n)
+    }
+
+    /// Increases the size of the filled region of the buffer.
+    ///
+    /// The number of initialized bytes is not changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the buffer unchanged, if the filled region of the buffer would become larger than
+    /// the initialized region.
+    #[inline]
+    pub fn try_add_filled(&mut self, n: usize) -> Result<(), InitError> {
+        self.borrow().try_add_filled(n)
     }
 
     /// Sets the size of the filled region of the buffer.
@@ -294,16 +467,86 @@ impl<S: Bytes> ReadBuf<S> {
     /// The number of initialized bytes is not changed.
     ///
     /// Note that this can be used to *shrink* the filled region of the buffer in addition to growing it (for
-    /// example, by a `Read` implementation that compresses data in-place).
+    /// example, by a `Read` implementation that compresses data in-place). Shrinking the filled region below the
+    /// consumed region also shrinks the consumed region.
     ///
     /// # Panics
     ///
-    /// Panics if the filled region of the buffer would become larger than the initialized region.
+    /// Panics if the filled region of the buffer would become larger than the initialized region. See
+    /// [`try_set_filled`](ReadBuf::try_set_filled) for a fallible version.
     #[inline]
+    #[track_caller]
     pub fn set_filled(&mut self, n: usize) {
-        assert!(n <= self.buf.len());
+        self.borrow().set_filled(n)
+    }
+
+    /// Sets the size of the filled region of the buffer.
+    ///
+    /// The number of initialized bytes is not changed. Shrinking the filled region below the consumed region also
+    /// shrinks the consumed region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the buffer unchanged, if the filled region of the buffer would become larger than
+    /// the initialized region.
+    #[inline]
+    pub fn try_set_filled(&mut self, n: usize) -> Result<(), InitError> {
+        self.borrow().try_set_filled(n)
+    }
+
+    /// Returns a shared reference to the filled portion of the buffer that has not yet been consumed.
+    #[inline]
+    pub fn unconsumed(&self) -> &[u8] {
+        &self.filled()[self.consumed..]
+    }
+
+    /// Marks `n` bytes of the filled region as consumed, so they are no longer returned by
+    /// [`unconsumed`](ReadBuf::unconsumed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the consumed region of the buffer would become larger than the filled region. See
+    /// [`try_consume`](ReadBuf::try_consume) for a fallible version.
+    #[inline]
+    #[track_caller]
+    pub fn consume(&mut self, n: usize) {
+        if let Err(e) = self.try_consume(n) {
+            panic!("{e}")
+        }
+    }
+
+    /// Marks `n` bytes of the filled region as consumed, so they are no longer returned by
+    /// [`unconsumed`](ReadBuf::unconsumed).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the buffer unchanged, if the consumed region of the buffer would become larger than
+    /// the filled region.
+    #[inline]
+    pub fn try_consume(&mut self, n: usize) -> Result<(), ConsumeError> {
+        let available = self.filled - self.consumed;
+        if n > available {
+            return Err(ConsumeError::new(n, available));
+        }
 
-        self.filled = n;
+        self.consumed += n;
+        Ok(())
+    }
+
+    /// Moves the unconsumed part of the filled region to the start of the buffer, discarding the consumed bytes.
+    ///
+    /// The number of initialized bytes is not changed, so the space freed at the end of the buffer never needs to
+    /// be initialized again.
+    #[inline]
+    pub fn compact(&mut self) {
+        if self.consumed == 0 {
+            return;
+        }
+
+        let consumed = self.consumed;
+        self.filled_mut().copy_within(consumed.., 0);
+        self.filled -= consumed;
+        self.consumed = 0;
     }
 
     /// Asserts that the first `n` unfilled bytes of the buffer are initialized.
@@ -316,27 +559,35 @@ impl<S: Bytes> ReadBuf<S> {
     /// The caller must ensure that the first `n` unfilled bytes of the buffer have already been initialized.
     #[inline]
     pub unsafe fn assume_init(&mut self, n: usize) {
-        self.buf
-            .set_len_unchecked(cmp::max(self.buf.len(), self.filled + n));
+        self.borrow().assume_init(n)
     }
 
     /// Appends data to the buffer, advancing the written position and possibly also the initialized position.
     ///
     /// # Panics
     ///
-    /// Panics if `self.remaining()` is less than `buf.len()`.
+    /// Panics if `self.remaining()` is less than `buf.len()`. See [`try_append`](ReadBuf::try_append) for a fallible
+    /// version.
     #[inline]
+    #[track_caller]
     pub fn append(&mut self, buf: &[u8]) {
-        assert!(self.remaining() >= buf.len());
+        self.borrow().append(buf)
+    }
 
-        // SAFETY: we do not de-initialize any of the elements of the slice
-        unsafe {
-            write_slice(&mut self.unfilled_mut()[..buf.len()], buf);
-        }
+    /// Appends data to the buffer, advancing the written position and possibly also the initialized position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without appending anything, if `self.remaining()` is less than `buf.len()`.
+    #[inline]
+    pub fn try_append(&mut self, buf: &[u8]) -> Result<(), CapacityError> {
+        self.borrow().try_append(buf)
+    }
 
-        // SAFETY: We just added the entire contents of buf to the filled section.
-        unsafe { self.assume_init(buf.len()) }
-        self.add_filled(buf.len());
+    /// Returns the amount of bytes that have been consumed.
+    #[inline]
+    pub fn consumed_len(&self) -> usize {
+        self.consumed
     }
 
     /// Returns the amount of bytes that have been filled.
@@ -348,7 +599,7 @@ impl<S: Bytes> ReadBuf<S> {
     /// Returns the amount of bytes that have been initialized.
     #[inline]
     pub fn initialized_len(&self) -> usize {
-        self.buf.len()
+        self.init
     }
 }
 
@@ -363,24 +614,82 @@ where
     this.copy_from_slice(uninit_src);
 }
 
-/// A wrapper around [`&mut ReadBuf`](ReadBuf) which prevents the buffer that the [`ReadBuf`] points to from being replaced.
-#[derive(Debug)]
-pub struct ReadBufRef<'a, S: Bytes> {
-    read_buf: &'a mut ReadBuf<S>,
+// from MaybeUninit::slice_assume_init_ref
+unsafe fn slice_assume_init<T>(slice: &[MaybeUninit<T>]) -> &[T] {
+    // SAFETY: &[T] and &[MaybeUninit<T>] have the same layout
+    &*(slice as *const [MaybeUninit<T>] as *const [T])
+}
+
+// from MaybeUninit::slice_assume_init_mut
+unsafe fn slice_assume_init_mut<T>(slice: &mut [MaybeUninit<T>]) -> &mut [T] {
+    // SAFETY: &mut [T] and &mut [MaybeUninit<T>] have the same layout
+    &mut *(slice as *mut [MaybeUninit<T>] as *mut [T])
+}
+
+/// A cursor into the buffer of a [`ReadBuf`], which prevents the buffer from being replaced.
+///
+/// The storage type of the [`ReadBuf`] is erased, so a single reader implementation works with every kind of
+/// [`ReadBuf`], and `Read` can be used as a trait object. A `ReadBufRef` borrows the whole buffer along with the
+/// counters of the [`ReadBuf`], so changes to the filled and initialized regions are visible to the [`ReadBuf`]
+/// straight away.
+pub struct ReadBufRef<'a> {
+    buf: &'a mut [MaybeUninit<u8>],
+    consumed: &'a mut usize,
+    filled: &'a mut usize,
+    init: &'a mut usize,
+    window: &'a mut usize,
+}
+
+impl fmt::Debug for ReadBufRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadBufRef")
+            .field("init", &*self.init)
+            .field("filled", &*self.filled)
+            .field("consumed", &*self.consumed)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
 }
 
-impl<'a, S: Bytes> ReadBufRef<'a, S> {
+impl<'a> ReadBufRef<'a> {
     /// Creates a new `ReadBufRef` referencing the same `ReadBuf` as this one.
-    pub fn reborrow(&mut self) -> ReadBufRef<'_, S> {
+    #[inline]
+    pub fn reborrow(&mut self) -> ReadBufRef<'_> {
         ReadBufRef {
-            read_buf: self.read_buf,
+            buf: self.buf,
+            consumed: self.consumed,
+            filled: self.filled,
+            init: self.init,
+            window: self.window,
         }
     }
 
+    /// Returns the total capacity of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns a shared reference to the filled portion of the buffer.
+    #[inline]
+    pub fn filled(&self) -> &[u8] {
+        &self.initialized()[..*self.filled]
+    }
+
     /// Returns a mutable reference to the filled portion of the buffer.
     #[inline]
     pub fn filled_mut(&mut self) -> &mut [u8] {
-        self.read_buf.filled_mut()
+        let filled = *self.filled;
+        &mut self.initialized_mut()[..filled]
+    }
+
+    /// Returns a shared reference to the initialized portion of the buffer.
+    ///
+    /// This includes the filled portion.
+    #[inline]
+    pub fn initialized(&self) -> &[u8] {
+        // SAFETY: the first `init` bytes of the buffer are initialized
+        unsafe { slice_assume_init(&self.buf[..*self.init]) }
     }
 
     /// Returns a mutable reference to the initialized portion of the buffer.
@@ -388,7 +697,8 @@ impl<'a, S: Bytes> ReadBufRef<'a, S> {
     /// This includes the filled portion.
     #[inline]
     pub fn initialized_mut(&mut self) -> &mut [u8] {
-        self.read_buf.initialized_mut()
+        // SAFETY: the first `init` bytes of the buffer are initialized
+        unsafe { slice_assume_init_mut(&mut self.buf[..*self.init]) }
     }
 
     /// Returns a mutable reference to the unfilled part of the buffer without ensuring that it has been fully
@@ -397,9 +707,14 @@ impl<'a, S: Bytes> ReadBufRef<'a, S> {
     /// # Safety
     ///
     /// The caller must not de-initialize portions of the buffer that have already been initialized.
+    ///
+    /// This is the slice that readers are given. Writing bytes never de-initializes them, so it can be handed to
+    /// anything that only writes to it. A `std::io::Read` that never reads from its buffer can implement
+    /// `TrustedRead` to be given this slice by `TrustedReader`, instead of a zeroed one. Its `read` must only write
+    /// to the slice, must return at most its length, and must not write past the length it returns.
     #[inline]
     pub unsafe fn unfilled_mut(&mut self) -> &mut [MaybeUninit<u8>] {
-        self.read_buf.unfilled_mut()
+        &mut self.buf[*self.filled..]
     }
 
     /// Returns a mutable reference to the uninitialized part of the buffer.
@@ -407,7 +722,7 @@ impl<'a, S: Bytes> ReadBufRef<'a, S> {
     /// It is safe to uninitialize any of these bytes.
     #[inline]
     pub fn uninitialized_mut(&mut self) -> &mut [MaybeUninit<u8>] {
-        self.read_buf.uninitialized_mut()
+        &mut self.buf[*self.init..]
     }
 
     /// Returns a mutable reference to the unfilled part of the buffer, ensuring it is fully initialized.
@@ -416,7 +731,8 @@ impl<'a, S: Bytes> ReadBufRef<'a, S> {
     /// the first use.
     #[inline]
     pub fn initialize_unfilled(&mut self) -> &mut [u8] {
-        self.read_buf.initialize_unfilled()
+        // the capacity check in `try_initialize_unfilled_to` always passes, so this never panics
+        self.initialize_unfilled_to(self.remaining())
     }
 
     /// Returns a mutable reference to the first `n` bytes of the unfilled part of the buffer, ensuring it is
@@ -424,18 +740,60 @@ impl<'a, S: Bytes> ReadBufRef<'a, S> {
     ///
     /// # Panics
     ///
-    /// Panics if `self.remaining()` is less than `n`.
+    /// Panics if `self.remaining()` is less than `n`. See
+    /// [`try_initialize_unfilled_to`](ReadBufRef::try_initialize_unfilled_to) for a fallible version.
     #[inline]
+    #[track_caller]
     pub fn initialize_unfilled_to(&mut self, n: usize) -> &mut [u8] {
-        self.read_buf.initialize_unfilled_to(n)
+        match self.try_initialize_unfilled_to(n) {
+            Ok(unfilled) => unfilled,
+            Err(e) => panic!("{e}"),
+        }
     }
 
-    /// Clears the buffer, resetting the filled region to empty.
+    /// Returns a mutable reference to the first `n` bytes of the unfilled part of the buffer, ensuring it is
+    /// fully initialized.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without initializing anything, if `self.remaining()` is less than `n`.
+    #[inline]
+    pub fn try_initialize_unfilled_to(&mut self, n: usize) -> Result<&mut [u8], CapacityError> {
+        if n > self.remaining() {
+            return Err(CapacityError::new(n, self.remaining()));
+        }
+
+        let extra_init = *self.init - *self.filled;
+        // If we don't have enough initialized, do zeroing
+        if n > extra_init {
+            let uninit = n - extra_init;
+            let unfilled = &mut self.uninitialized_mut()[0..uninit];
+            // SAFETY: `unfilled` is valid for writes of `uninit` bytes
+            unsafe { ptr::write_bytes(unfilled.as_mut_ptr(), 0, uninit) };
+
+            // SAFETY: we just initialized uninit bytes, and the previous bytes were already init
+            unsafe {
+                self.assume_init(n);
+            }
+        }
+
+        let filled = *self.filled;
+
+        Ok(&mut self.initialized_mut()[filled..filled + n])
+    }
+
+    /// Returns the number of bytes at the end of the slice that have not yet been filled.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - *self.filled
+    }
+
+    /// Clears the buffer, resetting the filled and consumed regions to empty.
     ///
     /// The number of initialized bytes is not changed, and the contents of the buffer are not modified.
     #[inline]
     pub fn clear(&mut self) {
-        self.read_buf.clear()
+        self.set_filled(0); // 0 is never past the initialized region, so this never panics
     }
 
     /// Increases the size of the filled region of the buffer.
@@ -444,10 +802,27 @@ impl<'a, S: Bytes> ReadBufRef<'a, S> {
     ///
     /// # Panics
     ///
-    /// Panics if the filled region of the buffer would become larger than the initialized region.
+    /// Panics if the filled region of the buffer would become larger than the initialized region. See
+    /// [`try_add_filled`](ReadBufRef::try_add_filled) for a fallible version.
     #[inline]
+    #[track_caller]
     pub fn add_filled(&mut self, n: usize) {
-        self.read_buf.add_filled(n)
+        if let Err(e) = self.try_add_filled(n) {
+            panic!("{e}")
+        }
+    }
+
+    /// Increases the size of the filled region of the buffer.
+    ///
+    /// The number of initialized bytes is not changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the buffer unchanged, if the filled region of the buffer would become larger than
+    /// the initialized region.
+    #[inline]
+    pub fn try_add_filled(&mut self, n: usize) -> Result<(), InitError> {
+        self.try_set_filled(self.filled.saturating_add(n))
     }
 
     /// Sets the size of the filled region of the buffer.
@@ -455,14 +830,39 @@ impl<'a, S: Bytes> ReadBufRef<'a, S> {
     /// The number of initialized bytes is not changed.
     ///
     /// Note that this can be used to *shrink* the filled region of the buffer in addition to growing it (for
-    /// example, by a `Read` implementation that compresses data in-place).
+    /// example, by a `Read` implementation that compresses data in-place). Shrinking the filled region below the
+    /// consumed region also shrinks the consumed region.
     ///
     /// # Panics
     ///
-    /// Panics if the filled region of the buffer would become larger than the initialized region.
+    /// Panics if the filled region of the buffer would become larger than the initialized region. See
+    /// [`try_set_filled`](ReadBufRef::try_set_filled) for a fallible version.
     #[inline]
+    #[track_caller]
     pub fn set_filled(&mut self, n: usize) {
-        self.read_buf.set_filled(n)
+        if let Err(e) = self.try_set_filled(n) {
+            panic!("{e}")
+        }
+    }
+
+    /// Sets the size of the filled region of the buffer.
+    ///
+    /// The number of initialized bytes is not changed. Shrinking the filled region below the consumed region also
+    /// shrinks the consumed region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the buffer unchanged, if the filled region of the buffer would become larger than
+    /// the initialized region.
+    #[inline]
+    pub fn try_set_filled(&mut self, n: usize) -> Result<(), InitError> {
+        if n > *self.init {
+            return Err(InitError::new(n, *self.init));
+        }
+
+        *self.filled = n;
+        *self.consumed = cmp::min(*self.consumed, n);
+        Ok(())
     }
 
     /// Asserts that the first `n` unfilled bytes of the buffer are initialized.
@@ -475,24 +875,82 @@ impl<'a, S: Bytes> ReadBufRef<'a, S> {
     /// The caller must ensure that the first `n` unfilled bytes of the buffer have already been initialized.
     #[inline]
     pub unsafe fn assume_init(&mut self, n: usize) {
-        self.read_buf.assume_init(n)
+        *self.init = cmp::max(*self.init, *self.filled + n);
     }
 
     /// Appends data to the buffer, advancing the written position and possibly also the initialized position.
     ///
     /// # Panics
     ///
-    /// Panics if `self.remaining()` is less than `buf.len()`.
+    /// Panics if `self.remaining()` is less than `buf.len()`. See [`try_append`](ReadBufRef::try_append) for a
+    /// fallible version.
     #[inline]
+    #[track_caller]
     pub fn append(&mut self, buf: &[u8]) {
-        self.read_buf.append(buf)
+        if let Err(e) = self.try_append(buf) {
+            panic!("{e}")
+        }
     }
-}
 
-impl<'a, S: Bytes> Deref for ReadBufRef<'a, S> {
-    type Target = ReadBuf<S>;
+    /// Appends data to the buffer, advancing the written position and possibly also the initialized position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without appending anything, if `self.remaining()` is less than `buf.len()`.
+    #[inline]
+    pub fn try_append(&mut self, buf: &[u8]) -> Result<(), CapacityError> {
+        if buf.len() > self.remaining() {
+            return Err(CapacityError::new(buf.len(), self.remaining()));
+        }
+
+        // SAFETY: we do not de-initialize any of the elements of the slice
+        unsafe {
+            write_slice(&mut self.unfilled_mut()[..buf.len()], buf);
+        }
+
+        // SAFETY: We just added the entire contents of buf to the filled section.
+        unsafe { self.assume_init(buf.len()) }
+        self.add_filled(buf.len());
+        Ok(())
+    }
+
+    /// Returns the amount of bytes that have been filled.
+    #[inline]
+    pub fn filled_len(&self) -> usize {
+        *self.filled
+    }
+
+    /// Returns the amount of bytes that have been initialized.
+    #[inline]
+    pub fn initialized_len(&self) -> usize {
+        *self.init
+    }
+
+    /// Lends at most the first `n` unfilled bytes of the buffer to `f`, so a reader can not read more than `n` bytes.
+    pub(crate) fn with_unfilled_to<T>(
+        &mut self,
+        n: usize,
+        f: impl FnOnce(ReadBufRef<'_>) -> T,
+    ) -> T {
+        if n >= self.remaining() {
+            return f(self.reborrow());
+        }
 
-    fn deref(&self) -> &ReadBuf<S> {
-        &*self.read_buf
+        let init = cmp::min(*self.init - *self.filled, n);
+        let window = *self.window;
+        // SAFETY: ReadSlice never de-initializes bytes
+        let mut buf = ReadSlice::from(&mut unsafe { self.unfilled_mut() }[..n]);
+        // SAFETY: the first `init` unfilled bytes are initialized
+        unsafe { buf.assume_init(init) };
+        buf.window = window;
+
+        let res = f(buf.borrow());
+        let (filled, init, window) = (buf.filled, buf.init, buf.window);
+
+        // SAFETY: ReadSlice tracked that these unfilled bytes have been initialized
+        unsafe { self.assume_init(init) };
+        self.add_filled(filled);
+        *self.window = window;
+        res
     }
 }
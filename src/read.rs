@@ -0,0 +1,738 @@
+//! The [`Read`] trait and its implementations
+
+use crate::{ReadArray, ReadBufRef, ReadVec};
+#[cfg(unix)]
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd};
+use std::{cmp, collections::VecDeque, fmt, fs, io, mem, net, process, slice, str};
+
+/// An extension of [`io::Read`] that can read into uninitialized buffers.
+///
+/// Readers that can fill a buffer without looking at its contents should override [`read_buf`](Read::read_buf)
+/// to avoid zeroing the unfilled region. Any other [`io::Read`] can be used through [`Reader`], which provides the
+/// default implementation, or through [`TrustedReader`] if it implements [`TrustedRead`].
+///
+/// On unix, files, sockets and child process pipes read directly into the unfilled region with `read(2)`, or with
+/// `readv(2)` for vectored reads, and [`io::Stdin`] copies out of its internal buffer. In-memory readers like
+/// `&[u8]`, [`io::Cursor`] and [`VecDeque`] append their bytes directly. None of them zero the buffer first.
+///
+/// Since [`ReadBufRef`] erases the storage of the buffer, `Read` can be used as a trait object. A `Box<dyn Read>` can
+/// be used anywhere a `Box<dyn io::Read>` can, and keeps the [`read_buf`](Read::read_buf) of the underlying reader.
+/// The implementations for `&mut R` and `Box<R>`, including `Box<dyn Read>`, forward every method, so wrapping a
+/// reader never loses its overrides.
+pub trait Read: io::Read {
+    /// Pull some bytes from this source into the specified buffer.
+    ///
+    /// This is equivalent to the [`read`](io::Read::read) method, except that it is passed a [`ReadBufRef`] rather than `[u8]` to allow use
+    /// with uninitialized buffers. The new data will be appended to any existing contents of `buf`.
+    ///
+    /// The default implementation delegates to `read`, zeroing a window at the start of the unfilled region rather
+    /// than all of it. The `ReadBuf` keeps track of the window, which doubles after each read that fills it, so a
+    /// large buffer is not zeroed for a small read. With the `nightly` feature, the window is passed to
+    /// [`io::Read::read_buf`] instead, so readers from the standard library do not zero it.
+    fn read_buf(&mut self, buf: ReadBufRef<'_>) -> io::Result<()> {
+        default_read_buf(self, buf)
+    }
+
+    /// Read the exact number of bytes required to fill `buf`.
+    ///
+    /// This is equivalent to the [`read_exact`](io::Read::read_exact) method, except that it is passed a [`ReadBufRef`] rather than `[u8]` to
+    /// allow use with uninitialized buffers.
+    fn read_buf_exact(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        while buf.remaining() > 0 {
+            let prev_filled = buf.filled().len();
+            match Read::read_buf(self, buf.reborrow()) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+
+            if buf.filled().len() == prev_filled {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "failed to fill buffer",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like [`read_buf`](Read::read_buf), except that it reads into a slice of buffers.
+    ///
+    /// This is equivalent to the [`read_vectored`](io::Read::read_vectored) method. Data is copied to fill each
+    /// buffer in order, with the final buffer written to possibly being only partially filled.
+    ///
+    /// The default implementation delegates to `read_vectored`.
+    fn read_buf_vectored(&mut self, bufs: &mut [ReadBufRef<'_>]) -> io::Result<()> {
+        default_read_buf_vectored(|b| self.read_vectored(b), bufs)
+    }
+
+    /// Pull some bytes from this source into `buf`, reserving more capacity first if it is full.
+    ///
+    /// This allows repeatedly reading into the same [`ReadVec`] without managing its capacity.
+    fn read_buf_grow(&mut self, buf: &mut ReadVec) -> io::Result<()> {
+        if buf.remaining() == 0 {
+            buf.reserve(DEFAULT_GROW);
+        }
+        Read::read_buf(self, buf.borrow())
+    }
+
+    /// Read all bytes until EOF in this source, appending them to `buf`.
+    ///
+    /// This is equivalent to the [`read_to_end`](io::Read::read_to_end) method, except that the buffer grows
+    /// geometrically through [`read_buf`](Read::read_buf), so spare capacity is never zeroed more than once.
+    /// Returns the number of bytes read.
+    ///
+    /// # Errors
+    ///
+    /// If `limit` is set and the source has more than `limit` bytes left, an error of kind
+    /// [`io::ErrorKind::InvalidData`] is returned. The bytes read so far are left in `buf`. Reads are capped so that
+    /// at most one byte past the limit is read, however much spare capacity `buf` has.
+    ///
+    /// If the buffer fails to grow, an error of kind [`io::ErrorKind::OutOfMemory`] is returned.
+    fn read_buf_to_end(&mut self, buf: &mut ReadVec, limit: Option<usize>) -> io::Result<usize> {
+        read_to_end_with(self, buf, limit, |_| Ok(()))
+    }
+
+    /// Read all bytes until EOF in this source, appending them to `buf`.
+    ///
+    /// This is equivalent to the [`read_to_string`](io::Read::read_to_string) method, except that the buffer grows
+    /// through [`read_buf`](Read::read_buf) like [`read_buf_to_end`](Read::read_buf_to_end), and only the newly
+    /// filled bytes are validated after each read. Returns the number of bytes read.
+    ///
+    /// # Errors
+    ///
+    /// If the data in this stream is not valid UTF-8, an error of kind [`io::ErrorKind::InvalidData`] is returned
+    /// as soon as the invalid sequence is read. The error wraps an [`InvalidUtf8`] with the offset of the sequence.
+    ///
+    /// Otherwise, the errors are the same as [`read_buf_to_end`](Read::read_buf_to_end).
+    ///
+    /// If an error is returned, `buf` is unchanged.
+    fn read_buf_to_string(&mut self, buf: &mut String, limit: Option<usize>) -> io::Result<usize> {
+        let start = buf.len();
+        let mut rbuf = ReadVec::from(mem::take(buf).into_bytes());
+        rbuf.set_filled(start);
+
+        let mut validated = start;
+        let res = read_to_end_with(self, &mut rbuf, limit, |rbuf| {
+            let filled = rbuf.filled();
+            match str::from_utf8(&filled[validated..]) {
+                Ok(_) => validated = filled.len(),
+                Err(e) => {
+                    validated += e.valid_up_to();
+                    // an incomplete sequence at the end might be completed by the next read
+                    if e.error_len().is_some() {
+                        return Err(invalid_utf8(validated - start));
+                    }
+                }
+            }
+            Ok(())
+        })
+        .and_then(|n| match validated - start {
+            validated if validated < n => Err(invalid_utf8(validated)),
+            _ => Ok(n),
+        });
+
+        let mut vec = Vec::from(rbuf);
+        if res.is_err() {
+            vec.truncate(start);
+        }
+        // SAFETY: the bytes up to `validated` are valid UTF-8, and the vec was truncated otherwise
+        *buf = unsafe { String::from_utf8_unchecked(vec) };
+        res
+    }
+}
+
+/// The error wrapped by [`Read::read_buf_to_string`] when the stream does not contain valid UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidUtf8 {
+    offset: usize,
+}
+
+impl InvalidUtf8 {
+    /// Returns the offset, from the start of the read, of the first invalid byte sequence
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for InvalidUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stream did not contain valid UTF-8 at byte offset {}",
+            self.offset
+        )
+    }
+}
+
+impl std::error::Error for InvalidUtf8 {}
+
+/// Reads to the end of `reader`, calling `check` with the buffer after every read that makes progress
+fn read_to_end_with<R: Read + ?Sized>(
+    reader: &mut R,
+    buf: &mut ReadVec,
+    limit: Option<usize>,
+    mut check: impl FnMut(&ReadVec) -> io::Result<()>,
+) -> io::Result<usize> {
+    let start = buf.filled_len();
+    let limit = limit.unwrap_or(usize::MAX);
+
+    loop {
+        if prepare_to_end(buf, start, limit)? {
+            return probe_to_end(probe(reader)?, buf.filled_len() - start);
+        }
+
+        // read at most one byte past the limit, which is enough to tell that the stream exceeds it
+        let prev_filled = buf.filled_len();
+        let max = (limit - (prev_filled - start)).saturating_add(1);
+        match buf
+            .borrow()
+            .with_unfilled_to(max, |buf| Read::read_buf(reader, buf))
+        {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+
+        if buf.filled_len() == prev_filled {
+            return Ok(prev_filled - start);
+        }
+        check(buf)?;
+    }
+}
+
+/// Makes room in `buf` for the next read of a `read_to_end` loop that started at `start`.
+///
+/// Returns `true` if the buffer is full at the limit, and the reader should be probed for EOF with
+/// [`probe_to_end`] instead.
+pub(crate) fn prepare_to_end(buf: &mut ReadVec, start: usize, limit: usize) -> io::Result<bool> {
+    let read = buf.filled_len() - start;
+    if read > limit {
+        return Err(too_large());
+    }
+
+    if buf.remaining() == 0 {
+        if read == limit {
+            return Ok(true);
+        }
+
+        let additional = cmp::min(cmp::max(buf.capacity(), DEFAULT_GROW), limit - read);
+        buf.try_reserve_exact(additional)
+            .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "failed to grow buffer"))?;
+    }
+    Ok(false)
+}
+
+/// Finishes a `read_to_end` loop that read `read` bytes, given the number of bytes read by a probe after
+/// reaching the limit
+pub(crate) fn probe_to_end(probed: usize, read: usize) -> io::Result<usize> {
+    match probed {
+        0 => Ok(read),
+        _ => Err(too_large()),
+    }
+}
+
+fn invalid_utf8(offset: usize) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, InvalidUtf8 { offset })
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream exceeded the maximum size",
+    )
+}
+
+/// Reads a single byte from `reader` to check whether it has reached EOF
+fn probe<R: Read + ?Sized>(reader: &mut R) -> io::Result<usize> {
+    let mut buf = ReadArray::<1>::new_uninit_array();
+    loop {
+        match Read::read_buf(reader, buf.borrow()) {
+            Ok(()) => return Ok(buf.filled_len()),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The minimum number of bytes reserved by [`Read::read_buf_grow`] once the buffer is full
+const DEFAULT_GROW: usize = 32;
+
+impl<R: Read + ?Sized> Read for &mut R {
+    #[inline]
+    fn read_buf(&mut self, buf: ReadBufRef<'_>) -> io::Result<()> {
+        Read::read_buf(&mut **self, buf)
+    }
+
+    #[inline]
+    fn read_buf_exact(&mut self, buf: ReadBufRef<'_>) -> io::Result<()> {
+        Read::read_buf_exact(&mut **self, buf)
+    }
+
+    #[inline]
+    fn read_buf_vectored(&mut self, bufs: &mut [ReadBufRef<'_>]) -> io::Result<()> {
+        Read::read_buf_vectored(&mut **self, bufs)
+    }
+
+    #[inline]
+    fn read_buf_grow(&mut self, buf: &mut ReadVec) -> io::Result<()> {
+        Read::read_buf_grow(&mut **self, buf)
+    }
+
+    #[inline]
+    fn read_buf_to_end(&mut self, buf: &mut ReadVec, limit: Option<usize>) -> io::Result<usize> {
+        Read::read_buf_to_end(&mut **self, buf, limit)
+    }
+
+    #[inline]
+    fn read_buf_to_string(&mut self, buf: &mut String, limit: Option<usize>) -> io::Result<usize> {
+        Read::read_buf_to_string(&mut **self, buf, limit)
+    }
+}
+
+impl<R: Read + ?Sized> Read for Box<R> {
+    #[inline]
+    fn read_buf(&mut self, buf: ReadBufRef<'_>) -> io::Result<()> {
+        Read::read_buf(&mut **self, buf)
+    }
+
+    #[inline]
+    fn read_buf_exact(&mut self, buf: ReadBufRef<'_>) -> io::Result<()> {
+        Read::read_buf_exact(&mut **self, buf)
+    }
+
+    #[inline]
+    fn read_buf_vectored(&mut self, bufs: &mut [ReadBufRef<'_>]) -> io::Result<()> {
+        Read::read_buf_vectored(&mut **self, bufs)
+    }
+
+    #[inline]
+    fn read_buf_grow(&mut self, buf: &mut ReadVec) -> io::Result<()> {
+        Read::read_buf_grow(&mut **self, buf)
+    }
+
+    #[inline]
+    fn read_buf_to_end(&mut self, buf: &mut ReadVec, limit: Option<usize>) -> io::Result<usize> {
+        Read::read_buf_to_end(&mut **self, buf, limit)
+    }
+
+    #[inline]
+    fn read_buf_to_string(&mut self, buf: &mut String, limit: Option<usize>) -> io::Result<usize> {
+        Read::read_buf_to_string(&mut **self, buf, limit)
+    }
+}
+
+impl Read for &[u8] {
+    #[inline]
+    fn read_buf(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        let (a, b) = self.split_at(cmp::min(self.len(), buf.remaining()));
+        buf.append(a);
+        *self = b;
+        Ok(())
+    }
+}
+
+impl<T: AsRef<[u8]>> Read for io::Cursor<T> {
+    #[inline]
+    fn read_buf(&mut self, buf: ReadBufRef<'_>) -> io::Result<()> {
+        let inner = self.get_ref().as_ref();
+        let start = cmp::min(self.position(), inner.len() as u64) as usize;
+        let mut remaining = &inner[start..];
+        Read::read_buf(&mut remaining, buf)?;
+        let read = inner.len() - start - remaining.len();
+        self.set_position(start as u64 + read as u64);
+        Ok(())
+    }
+}
+
+impl Read for VecDeque<u8> {
+    #[inline]
+    fn read_buf(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        let n = cmp::min(self.len(), buf.remaining());
+        let (front, back) = self.as_slices();
+        let split = cmp::min(front.len(), n);
+        buf.append(&front[..split]);
+        buf.append(&back[..n - split]);
+        self.drain(..n);
+        Ok(())
+    }
+}
+
+impl Read for io::Empty {
+    #[inline]
+    fn read_buf(&mut self, _buf: ReadBufRef<'_>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for io::Repeat {
+    fn read_buf(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        // the repeated byte is private, so read it out first
+        let mut byte = [0];
+        io::Read::read_exact(self, &mut byte)?;
+
+        let n = buf.remaining();
+        // SAFETY: every unfilled byte is written to, none are de-initialized
+        unsafe { buf.unfilled_mut() }.fill(mem::MaybeUninit::new(byte[0]));
+        // SAFETY: the whole unfilled region was initialized above
+        unsafe { buf.assume_init(n) };
+        buf.add_filled(n);
+        Ok(())
+    }
+}
+
+/// Implements [`Read`] for types that own a file descriptor.
+///
+/// On unix, `read_buf` passes the unfilled part of the buffer straight to `read(2)`, so it is never zeroed first.
+macro_rules! impl_read_fd {
+    ($($ty:ty),* $(,)?) => {$(
+        impl Read for $ty {
+            #[cfg(unix)]
+            #[inline]
+            fn read_buf(&mut self, buf: ReadBufRef<'_>) -> io::Result<()> {
+                read_buf_fd(self.as_fd(), buf)
+            }
+
+            #[cfg(unix)]
+            #[inline]
+            fn read_buf_vectored(&mut self, bufs: &mut [ReadBufRef<'_>]) -> io::Result<()> {
+                read_buf_vectored_fd(self.as_fd(), bufs)
+            }
+        }
+    )*};
+}
+
+impl_read_fd!(
+    fs::File,
+    &fs::File,
+    net::TcpStream,
+    &net::TcpStream,
+    process::ChildStdout,
+    process::ChildStderr,
+);
+#[cfg(unix)]
+impl_read_fd!(
+    std::os::unix::net::UnixStream,
+    &std::os::unix::net::UnixStream,
+);
+
+// Stdin is buffered by std, so reading from its file descriptor would skip any buffered bytes.
+// Instead, copy out of that buffer, which also never zeroes our buffer.
+impl Read for io::Stdin {
+    #[inline]
+    fn read_buf(&mut self, buf: ReadBufRef<'_>) -> io::Result<()> {
+        Read::read_buf(&mut self.lock(), buf)
+    }
+}
+
+impl Read for io::StdinLock<'_> {
+    fn read_buf(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        let available = io::BufRead::fill_buf(self)?;
+        let n = cmp::min(available.len(), buf.remaining());
+        buf.append(&available[..n]);
+        io::BufRead::consume(self, n);
+        Ok(())
+    }
+}
+impl<R: io::Read> Read for io::BufReader<R> {}
+impl<R: io::Read> Read for io::Take<R> {}
+impl<A: io::Read, B: io::Read> Read for io::Chain<A, B> {}
+
+/// Adapts any [`io::Read`] into a [`Read`] using the default, zero-initializing, [`read_buf`](Read::read_buf).
+#[derive(Debug, Default, Clone)]
+pub struct Reader<R> {
+    inner: R,
+}
+
+impl<R> Reader<R> {
+    /// Wraps the given reader
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwraps this `Reader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: io::Read> io::Read for Reader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+
+    #[cfg(feature = "nightly")]
+    #[inline]
+    fn read_buf(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+        self.inner.read_buf(cursor)
+    }
+}
+
+impl<R: io::Read> Read for Reader<R> {}
+
+/// Marks an [`io::Read`] that never reads from the buffer passed to [`read`](io::Read::read).
+///
+/// Wrapping such a reader in a [`TrustedReader`] gives it the unfilled region of a [`ReadBufRef`] directly, without
+/// zeroing it first. Implementing this trait alone does not change how the reader is read from, see
+/// [`TrustedReader`] for why.
+///
+/// # Safety
+///
+/// `read` must only write to its buffer, never read from it, and must return at most the length of the buffer. It
+/// must not write past the number of bytes it returns. The buffer it is given may be uninitialized.
+pub unsafe trait TrustedRead: io::Read {}
+
+unsafe impl<R: TrustedRead + ?Sized> TrustedRead for &mut R {}
+unsafe impl<R: TrustedRead + ?Sized> TrustedRead for Box<R> {}
+
+/// Adapts a [`TrustedRead`] into a [`Read`] whose [`read_buf`](Read::read_buf) never initializes the buffer.
+///
+/// `TrustedRead` readers are not picked up automatically: a blanket `Read` implementation for every `TrustedRead`
+/// would overlap with the implementations for `&mut R` and `Box<R>`, since other crates may implement `TrustedRead`
+/// for `&mut` or `Box` of their own types. Wrap the reader in a `TrustedReader` wherever a [`Read`] is needed
+/// instead.
+///
+/// In debug builds, the uninitialized bytes are filled with `0xA5` before each read, so a reader that breaks the
+/// contract sees the same recognisable bytes every time. Afterwards, the poisoned bytes past the ones that were
+/// read are checked, and a panic reports a reader that modified them.
+#[derive(Debug, Default, Clone)]
+pub struct TrustedReader<R> {
+    inner: R,
+}
+
+impl<R: TrustedRead> TrustedReader<R> {
+    /// Wraps the given reader
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R> TrustedReader<R> {
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwraps this `TrustedReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: TrustedRead> io::Read for TrustedReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+}
+
+unsafe impl<R: TrustedRead> TrustedRead for TrustedReader<R> {}
+
+impl<R: TrustedRead> Read for TrustedReader<R> {
+    fn read_buf(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        // the number of unfilled bytes that are already initialized, the rest are poisoned in debug builds
+        #[cfg(debug_assertions)]
+        let init = buf.initialized_len() - buf.filled_len();
+        #[cfg(debug_assertions)]
+        buf.uninitialized_mut().fill(mem::MaybeUninit::new(POISON));
+
+        // SAFETY: writing through the slice never de-initializes any bytes
+        let unfilled = unsafe { buf.unfilled_mut() };
+        let len = unfilled.len();
+        // SAFETY: the slice is valid for reads and writes of `len` bytes. Past `init` the bytes may be uninitialized,
+        // but `TrustedRead` guarantees that the reader only writes to them and never reads them.
+        let unfilled =
+            unsafe { slice::from_raw_parts_mut(unfilled.as_mut_ptr().cast::<u8>(), len) };
+        let n = self.inner.read(unfilled)?;
+        assert!(
+            n <= len,
+            "TrustedRead reader returned more bytes than requested"
+        );
+
+        // the poisoned bytes past the ones that were read must be untouched
+        #[cfg(debug_assertions)]
+        assert!(
+            unfilled[cmp::max(n, init)..].iter().all(|&b| b == POISON),
+            "TrustedRead reader modified the buffer past the bytes it returned"
+        );
+
+        // SAFETY: `TrustedRead` guarantees the reader wrote the first `n` bytes
+        unsafe { buf.assume_init(n) };
+        buf.add_filled(n);
+        Ok(())
+    }
+}
+
+/// The byte that [`TrustedReader`] fills uninitialized bytes with in debug builds
+#[cfg(debug_assertions)]
+const POISON: u8 = 0xA5;
+
+/// The smallest number of bytes that are initialized before a zeroing read
+const MIN_INIT_WINDOW: usize = 8 * 1024;
+
+/// Returns the size of the window at the start of the unfilled region for a zeroing read to fill.
+///
+/// The window is at least [`MIN_INIT_WINDOW`] bytes, or the size recorded by [`grow_window`], and covers any bytes
+/// that an earlier read left initialized.
+pub(crate) fn window_len(buf: &ReadBufRef<'_>) -> usize {
+    let spare = buf.initialized_len() - buf.filled_len();
+    let window = cmp::max(*buf.window, MIN_INIT_WINDOW);
+    cmp::min(cmp::max(spare, window), buf.remaining())
+}
+
+/// Records that `n` bytes were read into a window of `window` bytes.
+///
+/// Like the heuristics of std's `read_to_end`, a read that fills its window doubles the window for the next read,
+/// and short reads leave it as it is. Nothing is initialized until the next read needs it.
+pub(crate) fn grow_window(buf: &mut ReadBufRef<'_>, window: usize, n: usize) {
+    if n == window {
+        *buf.window = cmp::max(*buf.window, window.saturating_mul(2));
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+pub(crate) fn default_read_buf<R: io::Read + ?Sized>(
+    reader: &mut R,
+    mut buf: ReadBufRef<'_>,
+) -> io::Result<()> {
+    let window = window_len(&buf);
+    let n = reader.read(buf.initialize_unfilled_to(window))?;
+    buf.add_filled(n);
+    grow_window(&mut buf, window, n);
+    Ok(())
+}
+
+#[cfg(feature = "nightly")]
+pub(crate) fn default_read_buf<R: io::Read + ?Sized>(
+    reader: &mut R,
+    mut buf: ReadBufRef<'_>,
+) -> io::Result<()> {
+    let window = window_len(&buf);
+    let filled = buf.filled_len();
+    buf.with_cursor_to(window, |cursor| reader.read_buf(cursor))?;
+    let n = buf.filled_len() - filled;
+    grow_window(&mut buf, window, n);
+    Ok(())
+}
+
+// The largest read that `read(2)` accepts, matching std.
+#[cfg(unix)]
+#[cfg(target_vendor = "apple")]
+const READ_LIMIT: usize = libc::c_int::MAX as usize - 1;
+#[cfg(unix)]
+#[cfg(not(target_vendor = "apple"))]
+const READ_LIMIT: usize = libc::ssize_t::MAX as usize;
+
+/// Reads from `fd` into the unfilled part of `buf`, without initializing it first.
+#[cfg(unix)]
+fn read_buf_fd(fd: BorrowedFd<'_>, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+    // SAFETY: the kernel only writes to the unfilled bytes, it never de-initializes them
+    let unfilled = unsafe { buf.unfilled_mut() };
+    let len = cmp::min(unfilled.len(), READ_LIMIT);
+    // SAFETY: `unfilled` is valid for writes of `len` bytes
+    let ret = unsafe { libc::read(fd.as_raw_fd(), unfilled.as_mut_ptr().cast(), len) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let n = ret as usize;
+    // SAFETY: `read(2)` initialized the first `n` unfilled bytes
+    unsafe { buf.assume_init(n) };
+    buf.add_filled(n);
+    Ok(())
+}
+
+// The most buffers that `readv(2)` accepts, matching std.
+#[cfg(unix)]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MAX_IOV: usize = libc::UIO_MAXIOV as usize;
+#[cfg(unix)]
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const MAX_IOV: usize = 16;
+
+/// Reads from `fd` into the unfilled parts of `bufs` with `readv(2)`, without initializing them first.
+#[cfg(unix)]
+fn read_buf_vectored_fd(fd: BorrowedFd<'_>, bufs: &mut [ReadBufRef<'_>]) -> io::Result<()> {
+    let len = cmp::min(bufs.len(), MAX_IOV);
+    let bufs = &mut bufs[..len];
+    let iovecs: Vec<_> = bufs
+        .iter_mut()
+        .map(|buf| {
+            // SAFETY: the kernel only writes to the unfilled bytes, it never de-initializes them
+            let unfilled = unsafe { buf.unfilled_mut() };
+            libc::iovec {
+                iov_base: unfilled.as_mut_ptr().cast(),
+                iov_len: unfilled.len(),
+            }
+        })
+        .collect();
+    // SAFETY: each iovec is valid for writes of its length
+    let ret = unsafe { libc::readv(fd.as_raw_fd(), iovecs.as_ptr(), len as libc::c_int) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut n = ret as usize;
+    for buf in bufs {
+        let filled = cmp::min(n, buf.remaining());
+        // SAFETY: `readv(2)` initialized the first `filled` unfilled bytes of this buffer
+        unsafe { buf.assume_init(filled) };
+        buf.add_filled(filled);
+        n -= filled;
+    }
+    Ok(())
+}
+
+pub(crate) fn default_read_buf_vectored<F>(
+    read_vectored: F,
+    bufs: &mut [ReadBufRef<'_>],
+) -> io::Result<()>
+where
+    F: FnOnce(&mut [io::IoSliceMut<'_>]) -> io::Result<usize>,
+{
+    let mut slices: Vec<_> = bufs
+        .iter_mut()
+        .map(|buf| {
+            let window = window_len(buf);
+            io::IoSliceMut::new(buf.initialize_unfilled_to(window))
+        })
+        .collect();
+    let windows: Vec<_> = slices.iter().map(|slice| slice.len()).collect();
+    let mut n = read_vectored(&mut slices)?;
+    drop(slices);
+
+    for (buf, window) in bufs.iter_mut().zip(windows) {
+        let filled = cmp::min(n, window);
+        buf.add_filled(filled);
+        grow_window(buf, window, filled);
+        n -= filled;
+    }
+    Ok(())
+}
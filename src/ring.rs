@@ -0,0 +1,322 @@
+//! A circular [`ReadBuf`](crate::ReadBuf) variant
+
+use crate::{slice_assume_init_mut, write_slice, Bytes, ReadBuf};
+#[cfg(feature = "std")]
+use crate::{Read, ReadSlice};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use cl_generic_vec::{ArrayVec, SimpleVec};
+use core::{cmp, fmt, mem::MaybeUninit, ptr};
+#[cfg(feature = "std")]
+use std::io;
+
+/// A byte buffer that is filled and consumed in a circle.
+///
+/// Unlike [`ReadBuf`], the filled region can wrap around the end of the storage, so consuming bytes immediately makes
+/// space for new data without needing to [`compact`](ReadBuf::compact) the buffer. As a result, the filled and
+/// unfilled regions are each exposed as a pair of slices.
+///
+/// Initialization is tracked in the same way as [`ReadBuf`]. Data is always written sequentially around the ring,
+/// so the initialized region is a prefix of the storage, and every byte is zeroed at most once over the lifetime of
+/// the buffer.
+///
+/// Before wrapping, the contents of the buffer can be visualized as:
+/// ```not_rust
+/// [                  capacity                   ]
+/// [ unfilled |  filled  |        unfilled       ]
+/// [         initialized        | uninitialized  ]
+/// ```
+/// and once the filled region wraps, the whole buffer is initialized:
+/// ```not_rust
+/// [                  capacity                   ]
+/// [  filled  |      unfilled      |   filled    ]
+/// [                 initialized                 ]
+/// ```
+pub struct RingBuf<S: Bytes> {
+    head: usize,
+    len: usize,
+    buf: SimpleVec<S>,
+}
+
+impl<S: Bytes> fmt::Debug for RingBuf<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingBuf")
+            .field("init", &self.buf.len())
+            .field("head", &self.head)
+            .field("filled", &self.len)
+            .field("capacity", &self.buf.capacity())
+            .finish()
+    }
+}
+
+/// A [`RingBuf`] that takes it's buffer from an existing slice
+pub type RingSlice<'a> = RingBuf<&'a mut [MaybeUninit<u8>]>;
+/// A [`RingBuf`] that owns it's buffer using a boxed slice
+#[cfg(feature = "alloc")]
+pub type RingVec = RingBuf<Box<[MaybeUninit<u8>]>>;
+/// A [`RingBuf`] that owns it's buffer using a [`[MaybeUninit<u8>; N]`](array)
+pub type RingArray<const N: usize> = RingBuf<[MaybeUninit<u8>; N]>;
+
+impl<const N: usize> RingArray<N> {
+    /// Create a new uninitialised [`RingBuf`] backed by an array
+    /// Will begin with 0 filled bytes.
+    pub fn new_uninit_array() -> Self {
+        Self {
+            head: 0,
+            len: 0,
+            buf: ArrayVec::new(),
+        }
+    }
+}
+
+/// Create a [`RingBuf`] from a [`ReadBuf`].
+/// The unconsumed bytes of the [`ReadBuf`] become the filled bytes of the ring.
+impl<S: Bytes> From<ReadBuf<S>> for RingBuf<S> {
+    fn from(buf: ReadBuf<S>) -> Self {
+        let len = buf.filled - buf.consumed;
+        RingBuf {
+            // keep the unfilled region contiguous while the buffer is empty, like `consume`
+            head: if len == 0 { 0 } else { buf.consumed },
+            len,
+            buf: buf.into_vec(),
+        }
+    }
+}
+
+impl<S: Bytes> RingBuf<S> {
+    /// Returns the total capacity of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Returns the amount of bytes that have been filled.
+    #[inline]
+    pub fn filled_len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the amount of bytes that have been initialized.
+    #[inline]
+    pub fn initialized_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns the number of bytes that have not yet been filled.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.len
+    }
+
+    /// Returns the position one past the end of the filled region, without wrapping.
+    #[inline]
+    fn end(&self) -> usize {
+        self.head + self.len
+    }
+
+    /// Returns shared references to the filled portion of the buffer, in order.
+    ///
+    /// The second slice is only non-empty when the filled region wraps around the end of the storage.
+    #[inline]
+    pub fn filled(&self) -> (&[u8], &[u8]) {
+        let end = self.end();
+        if end <= self.capacity() {
+            (&self.buf[self.head..end], &[])
+        } else {
+            let (wrapped, head) = self.buf.split_at(self.head);
+            (head, &wrapped[..end - self.capacity()])
+        }
+    }
+
+    /// Returns mutable references to the filled portion of the buffer, in order.
+    ///
+    /// The second slice is only non-empty when the filled region wraps around the end of the storage.
+    #[inline]
+    pub fn filled_mut(&mut self) -> (&mut [u8], &mut [u8]) {
+        let end = self.end();
+        let capacity = self.capacity();
+        let head = self.head;
+        if end <= capacity {
+            (&mut self.buf[head..end], &mut [])
+        } else {
+            let (wrapped, head) = self.buf.split_at_mut(head);
+            (head, &mut wrapped[..end - capacity])
+        }
+    }
+
+    /// Returns mutable references to the unfilled part of the buffer, in order, without ensuring that it has been
+    /// fully initialized.
+    ///
+    /// The second slice is only non-empty when the unfilled region wraps around the end of the storage.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not de-initialize portions of the buffer that have already been initialized.
+    #[inline]
+    pub unsafe fn unfilled_mut(&mut self) -> (&mut [MaybeUninit<u8>], &mut [MaybeUninit<u8>]) {
+        let end = self.end();
+        let capacity = self.capacity();
+        let head = self.head;
+        let storage = self.buf.storage_mut().as_mut();
+        if end < capacity {
+            let (wrapped, tail) = storage.split_at_mut(end);
+            (tail, &mut wrapped[..head])
+        } else {
+            (&mut storage[end - capacity..head], &mut [])
+        }
+    }
+
+    /// Returns mutable references to the unfilled part of the buffer, in order, ensuring it is fully initialized.
+    ///
+    /// The second slice is only non-empty when the unfilled region wraps around the end of the storage.
+    ///
+    /// Since `RingBuf` tracks the region of the buffer that has been initialized, this is effectively "free" after
+    /// the first use.
+    #[inline]
+    pub fn initialize_unfilled(&mut self) -> (&mut [u8], &mut [u8]) {
+        // every uninitialized byte is part of the unfilled region
+        let spare = self.buf.spare_capacity_mut();
+        // SAFETY: `spare` is valid for writes of its length
+        unsafe { ptr::write_bytes(spare.as_mut_ptr(), 0, spare.len()) };
+        // SAFETY: we just initialized the rest of the buffer
+        unsafe {
+            self.buf.set_len_unchecked(self.buf.capacity());
+        }
+
+        // SAFETY: the whole storage is initialized
+        let (tail, wrapped) = unsafe { self.unfilled_mut() };
+        unsafe { (slice_assume_init_mut(tail), slice_assume_init_mut(wrapped)) }
+    }
+
+    /// Increases the size of the filled region of the buffer.
+    ///
+    /// The number of initialized bytes is not changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filled region of the buffer would become larger than the initialized region.
+    #[inline]
+    pub fn add_filled(&mut self, n: usize) {
+        assert!(n <= self.remaining());
+        assert!(cmp::min(self.end() + n, self.capacity()) <= self.buf.len());
+
+        self.len += n;
+    }
+
+    /// Marks `n` bytes at the start of the filled region as consumed, making space for them to be filled again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the filled region.
+    #[inline]
+    pub fn consume(&mut self, n: usize) {
+        assert!(n <= self.len);
+
+        self.len -= n;
+        self.head = if self.len == 0 {
+            // keep the unfilled region contiguous while the buffer is empty
+            0
+        } else {
+            let head = self.head + n;
+            if head >= self.capacity() {
+                head - self.capacity()
+            } else {
+                head
+            }
+        };
+    }
+
+    /// Clears the buffer, resetting the filled region to empty.
+    ///
+    /// The number of initialized bytes is not changed, and the contents of the buffer are not modified.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Asserts that the first `n` unfilled bytes of the buffer are initialized.
+    ///
+    /// `RingBuf` assumes that bytes are never de-initialized, so this method does nothing when called with fewer
+    /// bytes than are already known to be initialized.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the first `n` unfilled bytes of the buffer have already been initialized.
+    #[inline]
+    pub unsafe fn assume_init(&mut self, n: usize) {
+        let init = cmp::min(self.end() + n, self.capacity());
+        self.buf.set_len_unchecked(cmp::max(self.buf.len(), init));
+    }
+
+    /// Appends data to the buffer, advancing the written position and possibly also the initialized position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self.remaining()` is less than `buf.len()`.
+    #[inline]
+    pub fn append(&mut self, buf: &[u8]) {
+        assert!(self.remaining() >= buf.len());
+
+        // SAFETY: we do not de-initialize any of the elements of the slice
+        unsafe {
+            let (tail, wrapped) = self.unfilled_mut();
+            let n = cmp::min(tail.len(), buf.len());
+            write_slice(&mut tail[..n], &buf[..n]);
+            write_slice(&mut wrapped[..buf.len() - n], &buf[n..]);
+        }
+
+        // SAFETY: We just added the entire contents of buf to the filled section.
+        unsafe { self.assume_init(buf.len()) }
+        self.add_filled(buf.len());
+    }
+
+    /// Pull some bytes from `reader` into the unfilled region of the buffer, returning how many bytes were read.
+    ///
+    /// Each unfilled segment is lent to `reader` as a [`ReadSlice`](crate::ReadSlice) that knows how much of it is
+    /// initialized, so a reader with an uninitialized [`read_buf`](Read::read_buf) never zeroes the ring. When the
+    /// unfilled region wraps around the end of the storage, both segments are passed to
+    /// [`read_buf_vectored`](Read::read_buf_vectored) so readers that support vectored reads can fill them in one
+    /// call.
+    #[cfg(feature = "std")]
+    pub fn read_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<usize> {
+        // the initialized region is a prefix of the storage, and the filled region is inside it
+        let init = self.buf.len();
+        let end = self.end();
+        let tail_start = if end < self.capacity() {
+            end
+        } else {
+            end - self.capacity()
+        };
+
+        // SAFETY: ReadSlice never de-initializes bytes
+        let (tail, wrapped) = unsafe { self.unfilled_mut() };
+        let (tail_len, wrapped_len) = (tail.len(), wrapped.len());
+        let mut tail = ReadSlice::from(tail);
+        let mut wrapped = ReadSlice::from(wrapped);
+        // SAFETY: the storage is initialized up to `init`, and the wrapped segment starts before the filled region
+        unsafe {
+            tail.assume_init(cmp::min(init.saturating_sub(tail_start), tail_len));
+            wrapped.assume_init(wrapped_len);
+        }
+
+        if wrapped_len == 0 {
+            Read::read_buf(reader, tail.borrow())?;
+        } else {
+            reader.read_buf_vectored(&mut [tail.borrow(), wrapped.borrow()])?;
+        }
+
+        let (tail_filled, tail_init) = (tail.filled_len(), tail.initialized_len());
+        let wrapped_filled = wrapped.filled_len();
+        assert!(
+            wrapped_filled == 0 || tail_filled == tail_len,
+            "the wrapped segment was read into before the tail was full"
+        );
+
+        // SAFETY: ReadSlice tracked that these bytes of the tail segment have been initialized
+        unsafe { self.assume_init(tail_init) };
+        let n = tail_filled + wrapped_filled;
+        self.add_filled(n);
+        Ok(n)
+    }
+}
@@ -0,0 +1,156 @@
+//! Integration with [`tokio::io::AsyncRead`]
+
+use crate::{ReadBufExact, ReadBufFuture, ReadBufRef, ReadBufToEnd, ReadSlice, ReadVec};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::AsyncRead;
+
+impl ReadBufRef<'_> {
+    /// Lends the unfilled region of this buffer to `f` as a [`tokio::io::ReadBuf`].
+    ///
+    /// The tokio buffer starts empty, with the initialized bytes of the unfilled region already marked as
+    /// initialized. Afterwards, both the filled and initialized counts of the tokio buffer are copied back, so
+    /// uninitialized memory stays uninitialized across the tokio boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` replaces the tokio buffer with a different one.
+    pub fn with_tokio<T>(&mut self, f: impl FnOnce(&mut tokio::io::ReadBuf<'_>) -> T) -> T {
+        let init = self.initialized_len() - self.filled_len();
+
+        // SAFETY: tokio's ReadBuf never de-initializes bytes
+        let mut tokio_buf = tokio::io::ReadBuf::uninit(unsafe { self.unfilled_mut() });
+        // SAFETY: the first `init` bytes of the unfilled region are initialized
+        unsafe { tokio_buf.assume_init(init) };
+
+        let ptr = tokio_buf.filled().as_ptr();
+        let res = f(&mut tokio_buf);
+        assert_eq!(
+            ptr,
+            tokio_buf.filled().as_ptr(),
+            "the tokio buffer was replaced"
+        );
+        let (filled, init) = (tokio_buf.filled().len(), tokio_buf.initialized().len());
+
+        // SAFETY: tokio tracked that these bytes have been initialized
+        unsafe { self.assume_init(init) };
+        self.add_filled(filled);
+        res
+    }
+}
+
+/// Lends the unfilled region of a [`tokio::io::ReadBuf`] to `f` as a [`ReadBufRef`].
+///
+/// This is the reverse of [`ReadBufRef::with_tokio`]. The [`ReadBufRef`] starts empty, with the initialized bytes of
+/// the unfilled region already marked as initialized. Afterwards, both the filled and initialized counts are copied
+/// back to the tokio buffer.
+pub fn with_read_buf<T>(
+    tokio_buf: &mut tokio::io::ReadBuf<'_>,
+    f: impl FnOnce(ReadBufRef<'_>) -> T,
+) -> T {
+    let init = tokio_buf.initialized().len() - tokio_buf.filled().len();
+
+    let (res, filled, init) = {
+        // SAFETY: ReadBuf never de-initializes bytes
+        let mut buf = ReadSlice::from(unsafe { tokio_buf.unfilled_mut() });
+        // SAFETY: the first `init` bytes of the unfilled region are initialized
+        unsafe { buf.assume_init(init) };
+
+        let res = f(buf.borrow());
+        (res, buf.filled_len(), buf.initialized_len())
+    };
+
+    // SAFETY: ReadBuf tracked that these bytes have been initialized
+    unsafe { tokio_buf.assume_init(init) };
+    tokio_buf.advance(filled);
+    res
+}
+
+/// Pull some bytes from `reader` into `buf`, through a [`tokio::io::ReadBuf`] over the unfilled region.
+///
+/// See [`ReadBufRef::with_tokio`] for how the buffers are kept in sync.
+pub fn poll_read_buf<R: AsyncRead + ?Sized>(
+    reader: Pin<&mut R>,
+    cx: &mut Context<'_>,
+    mut buf: ReadBufRef<'_>,
+) -> Poll<io::Result<()>> {
+    buf.with_tokio(|tokio_buf| reader.poll_read(cx, tokio_buf))
+}
+
+/// Marks a [`Compat`] adapter for [`AsyncRead`].
+#[derive(Debug)]
+pub enum Tokio {}
+
+/// An adapter between [`AsyncRead`] and this crate's [`AsyncRead`](crate::AsyncRead).
+///
+/// `Compat<R>` implements [`crate::AsyncRead`] when `R` implements [`AsyncRead`], reading through
+/// [`ReadBufRef::with_tokio`]. In the other direction, it implements [`AsyncRead`] when `R` implements
+/// [`crate::AsyncRead`], reading through [`with_read_buf`]. Either way, uninitialized memory is never initialized.
+pub type Compat<R> = crate::Compat<R, Tokio>;
+
+impl<R: AsyncRead> crate::AsyncRead for Compat<R> {
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufRef<'_>,
+    ) -> Poll<io::Result<()>> {
+        poll_read_buf(self.project(), cx, buf)
+    }
+}
+
+impl<R: crate::AsyncRead> AsyncRead for Compat<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        with_read_buf(buf, |buf| self.project().poll_read_buf(cx, buf))
+    }
+}
+
+/// An extension of [`AsyncRead`] that can read into uninitialized [`ReadBuf`](crate::ReadBuf)s.
+///
+/// The futures are shared with [`AsyncReadExt`](crate::AsyncReadExt), reading through a [`Compat`] adapter.
+pub trait AsyncReadBufExt: AsyncRead {
+    /// Pull some bytes from this source into the specified buffer.
+    ///
+    /// This is the async equivalent of [`Read::read_buf`](crate::Read::read_buf).
+    fn read_buf<'a>(&'a mut self, buf: ReadBufRef<'a>) -> ReadBufFuture<'a, Compat<&'a mut Self>>
+    where
+        Self: Unpin,
+    {
+        ReadBufFuture::new(Compat::new(self), buf)
+    }
+
+    /// Read the exact number of bytes required to fill `buf`.
+    ///
+    /// This is the async equivalent of [`Read::read_buf_exact`](crate::Read::read_buf_exact).
+    fn read_buf_exact<'a>(
+        &'a mut self,
+        buf: ReadBufRef<'a>,
+    ) -> ReadBufExact<'a, Compat<&'a mut Self>>
+    where
+        Self: Unpin,
+    {
+        ReadBufExact::new(Compat::new(self), buf)
+    }
+
+    /// Read all bytes until EOF in this source, appending them to `buf`.
+    ///
+    /// This is the async equivalent of [`Read::read_buf_to_end`](crate::Read::read_buf_to_end).
+    fn read_buf_to_end<'a>(
+        &'a mut self,
+        buf: &'a mut ReadVec,
+        limit: Option<usize>,
+    ) -> ReadBufToEnd<'a, Compat<&'a mut Self>>
+    where
+        Self: Unpin,
+    {
+        ReadBufToEnd::new(Compat::new(self), buf, limit)
+    }
+}
+
+impl<R: AsyncRead + ?Sized> AsyncReadBufExt for R {}
@@ -0,0 +1,146 @@
+use cl_generic_read_buf::{AsyncRead, AsyncReadExt, ReadArray, ReadBufRef, ReadVec};
+
+use futures::{
+    executor::block_on,
+    future::{poll_fn, FutureExt},
+};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A reader that yields its chunks one at a time without initializing the rest of the buffer,
+/// returning `Interrupted` and `Pending` between each of them
+struct Chunked<'a> {
+    chunks: Vec<&'a [u8]>,
+    interrupted: bool,
+    pending: bool,
+}
+
+impl<'a> Chunked<'a> {
+    fn new(chunks: &[&'a [u8]]) -> Self {
+        Self {
+            chunks: chunks.to_vec(),
+            interrupted: false,
+            pending: false,
+        }
+    }
+}
+
+impl AsyncRead for Chunked<'_> {
+    fn poll_read_buf(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        mut buf: ReadBufRef<'_>,
+    ) -> Poll<io::Result<()>> {
+        if !std::mem::replace(&mut self.pending, true) {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        if !std::mem::replace(&mut self.interrupted, true) {
+            return Poll::Ready(Err(io::ErrorKind::Interrupted.into()));
+        }
+        self.pending = false;
+        self.interrupted = false;
+
+        if let Some(chunk) = self.chunks.first_mut() {
+            let n = chunk.len().min(buf.remaining());
+            buf.append(&chunk[..n]);
+            *chunk = &chunk[n..];
+            if chunk.is_empty() {
+                self.chunks.remove(0);
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn read_buf() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    let mut reader = Chunked::new(&[b"12", b"345"]);
+
+    let err = block_on(reader.read_buf(buf.borrow())).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+    block_on(reader.read_buf(buf.borrow())).unwrap();
+    assert_eq!(buf.filled(), b"12");
+    assert_eq!(buf.initialized_len(), 2);
+}
+
+#[test]
+fn read_buf_exact() {
+    let mut buf = ReadArray::<4>::new_uninit_array();
+    let mut reader = Chunked::new(&[b"12", b"34", b"56"]);
+
+    block_on(reader.read_buf_exact(buf.borrow())).unwrap();
+    assert_eq!(buf.filled(), b"1234");
+    assert_eq!(buf.initialized_len(), 4);
+
+    buf.clear();
+    let err = block_on(reader.read_buf_exact(buf.borrow())).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(buf.filled(), b"56");
+}
+
+#[test]
+fn read_buf_to_end() {
+    let mut buf = ReadVec::from(Vec::new());
+    let mut reader = Chunked::new(&[&[1; 50], &[2; 50]]);
+    assert_eq!(
+        block_on(reader.read_buf_to_end(&mut buf, None)).unwrap(),
+        100
+    );
+    assert_eq!(&buf.filled()[..50], [1; 50]);
+    assert_eq!(&buf.filled()[50..], [2; 50]);
+
+    let mut buf = ReadVec::from(Vec::new());
+    let mut reader = Chunked::new(&[&[1; 50], &[2; 51]]);
+    let err = block_on(reader.read_buf_to_end(&mut buf, Some(100))).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut buf = ReadVec::from(Vec::new());
+    let mut reader = Chunked::new(&[&[1; 50], &[2; 50]]);
+    assert_eq!(
+        block_on(reader.read_buf_to_end(&mut buf, Some(100))).unwrap(),
+        100
+    );
+
+    // a larger existing buffer can not be used to go over the limit
+    let mut buf = ReadVec::from(Vec::with_capacity(1024));
+    let mut reader = Chunked::new(&[&[1; 500]]);
+    let err = block_on(reader.read_buf_to_end(&mut buf, Some(100))).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(buf.filled_len() <= 101);
+}
+
+#[test]
+fn forwarding() {
+    let mut buf = ReadArray::<2>::new_uninit_array();
+    let mut reader = Box::new(Chunked::new(&[b"12"]));
+    block_on(reader.read_buf_exact(buf.borrow())).unwrap();
+    assert_eq!(buf.filled(), b"12");
+
+    let mut buf = ReadArray::<2>::new_uninit_array();
+    let mut reader = Box::pin(Chunked::new(&[b"34"]));
+    block_on(reader.read_buf_exact(buf.borrow())).unwrap();
+    assert_eq!(buf.filled(), b"34");
+}
+
+#[test]
+fn cancellation() {
+    let mut buf = ReadArray::<4>::new_uninit_array();
+    let mut reader = Chunked::new(&[b"1"]);
+
+    {
+        let mut fut = reader.read_buf_exact(buf.borrow());
+        // pending, interrupted and then the first chunk
+        let poll = block_on(poll_fn(|cx| Poll::Ready(fut.poll_unpin(cx))));
+        assert!(poll.is_pending());
+        let poll = block_on(poll_fn(|cx| Poll::Ready(fut.poll_unpin(cx))));
+        assert!(poll.is_pending());
+    }
+
+    // the byte read before the future was dropped is kept
+    assert_eq!(buf.filled(), b"1");
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "nightly")]
+#![feature(core_io_borrowed_buf, borrowed_buf_init)]
+
+use cl_generic_read_buf::{borrowed_buf::with_read_buf, Read, ReadArray, ReadVec, Reader};
+
+use std::{io::BorrowedBuf, mem::MaybeUninit};
+
+#[test]
+fn with_cursor() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    buf.append(b"ab");
+
+    buf.borrow().with_cursor(|mut cursor| {
+        assert_eq!(cursor.capacity(), 14);
+        assert!(!cursor.is_init());
+        cursor.append(b"cd");
+    });
+    assert_eq!(buf.filled(), b"abcd");
+    assert_eq!(buf.initialized_len(), 4);
+
+    buf.borrow().with_cursor(|mut cursor| {
+        cursor.ensure_init();
+        cursor.append(b"ef");
+    });
+    assert_eq!(buf.filled(), b"abcdef");
+    assert_eq!(buf.initialized_len(), 16);
+
+    buf.borrow().with_cursor(|cursor| assert!(cursor.is_init()));
+}
+
+#[test]
+fn with_read_buf_from_cursor() {
+    let mut storage = [MaybeUninit::uninit(); 16];
+    let mut borrowed = BorrowedBuf::from(&mut storage[..]);
+    let mut cursor = borrowed.unfilled();
+
+    with_read_buf(&mut cursor, |mut buf| {
+        assert_eq!(buf.filled(), b"");
+        assert_eq!(buf.initialized_len(), 0);
+        assert_eq!(buf.capacity(), 16);
+
+        buf.append(b"ab");
+    });
+    assert!(!cursor.is_init());
+
+    with_read_buf(&mut cursor, |mut buf| {
+        buf.initialize_unfilled();
+        buf.append(b"cd");
+    });
+    assert!(cursor.is_init());
+
+    assert_eq!(borrowed.filled(), b"abcd");
+    assert!(borrowed.is_init());
+}
+
+#[test]
+fn read_buf_uses_std() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    // std's implementation for slices does not initialize the rest of the buffer
+    let mut reader = Reader::new(&b"abc"[..]);
+    Read::read_buf(&mut reader, buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), b"abc");
+    assert_eq!(buf.initialized_len(), 3);
+}
+
+#[test]
+fn read_buf_window_uses_std() {
+    let mut buf = ReadVec::from(Vec::with_capacity(1 << 20));
+
+    // filling the window does not initialize the next one up front
+    let mut reader = Reader::new(&[1u8; 8192][..]);
+    Read::read_buf(&mut reader, buf.borrow()).unwrap();
+    assert_eq!(buf.filled_len(), 8192);
+    assert_eq!(buf.initialized_len(), 8192);
+
+    // nor does a read at EOF
+    Read::read_buf(&mut reader, buf.borrow()).unwrap();
+    assert_eq!(buf.filled_len(), 8192);
+    assert_eq!(buf.initialized_len(), 8192);
+}
@@ -0,0 +1,80 @@
+use cl_generic_read_buf::{BufReader, Read, ReadArray, ReadSlice, ReadVec};
+
+use std::{
+    io::{self, BufRead, Cursor},
+    mem::MaybeUninit,
+};
+
+#[test]
+fn read_lines_array() {
+    let c = Cursor::new(&b"hello\nworld\n"[..]);
+    let mut reader = BufReader::with_buffer(c, ReadArray::<4>::new_uninit_array());
+
+    let lines: Vec<String> = (&mut reader).lines().map(Result::unwrap).collect();
+    assert_eq!(lines, ["hello", "world"]);
+}
+
+#[test]
+fn read_lines_slice() {
+    let mut buf = [MaybeUninit::uninit(); 4];
+    let c = Cursor::new(&b"hello\nworld\n"[..]);
+    let reader = BufReader::with_buffer(c, ReadSlice::from(&mut buf[..]));
+
+    let lines: Vec<String> = reader.lines().map(Result::unwrap).collect();
+    assert_eq!(lines, ["hello", "world"]);
+}
+
+#[test]
+fn fill_buf() {
+    let c = Cursor::new(&b"123456789"[..]);
+    let mut reader = BufReader::with_capacity(4, c);
+
+    assert_eq!(reader.buffer(), b"");
+    assert_eq!(reader.fill_buf().unwrap(), b"1234");
+
+    reader.consume(1);
+    assert_eq!(reader.buffer(), b"234");
+    // doesn't read more until the buffer is consumed
+    assert_eq!(reader.fill_buf().unwrap(), b"234");
+
+    reader.consume(3);
+    assert_eq!(reader.fill_buf().unwrap(), b"5678");
+
+    let (_, buf) = reader.into_parts();
+    assert_eq!(buf.unconsumed(), b"5678");
+    // filling the buffer again did not initialize it again
+    assert_eq!(buf.initialized_len(), 4);
+}
+
+#[test]
+fn read() {
+    let c = Cursor::new(&b"123456789"[..]);
+    let mut reader = BufReader::with_capacity(4, c);
+
+    let mut out = [0; 2];
+    assert_eq!(io::Read::read(&mut reader, &mut out).unwrap(), 2);
+    assert_eq!(out, *b"12");
+    assert_eq!(reader.buffer(), b"34");
+
+    // bypasses the buffer once it's empty
+    let mut out = [0; 4];
+    assert_eq!(io::Read::read(&mut reader, &mut out).unwrap(), 2);
+    assert_eq!(io::Read::read(&mut reader, &mut out).unwrap(), 4);
+    assert_eq!(out, *b"5678");
+    assert_eq!(reader.buffer(), b"");
+}
+
+#[test]
+fn read_buf() {
+    let c = Cursor::new(&b"123456789"[..]);
+    let mut reader = BufReader::with_buffer(c, ReadArray::<4>::new_uninit_array());
+
+    let mut buf = ReadVec::from(Vec::with_capacity(2));
+    reader.read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), b"12");
+    assert_eq!(reader.buffer(), b"34");
+
+    let mut buf = ReadVec::from(Vec::with_capacity(16));
+    reader.read_buf_exact(buf.borrow()).unwrap_err();
+    assert_eq!(buf.filled(), b"3456789");
+}
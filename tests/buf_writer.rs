@@ -0,0 +1,102 @@
+use cl_generic_read_buf::{BufWriter, ReadArray};
+
+use std::io::{self, Write};
+
+/// A writer that accepts at most `n` bytes per write
+#[derive(Debug)]
+struct Short(Vec<u8>, usize);
+
+impl Write for Short {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.1);
+        self.0.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn write_array() {
+    let mut writer = BufWriter::with_buffer(Vec::new(), ReadArray::<4>::new_uninit_array());
+
+    writer.write_all(b"ab").unwrap();
+    writer.write_all(b"cd").unwrap();
+    assert_eq!(writer.buffer(), b"abcd");
+    assert_eq!(writer.get_ref(), b"");
+
+    // doesn't fit, so the buffer is flushed first
+    writer.write_all(b"e").unwrap();
+    assert_eq!(writer.buffer(), b"e");
+    assert_eq!(writer.get_ref(), b"abcd");
+
+    writer.flush().unwrap();
+    assert_eq!(writer.buffer(), b"");
+    assert_eq!(writer.get_ref(), b"abcde");
+}
+
+#[test]
+fn write_large() {
+    let mut writer = BufWriter::with_capacity(4, Vec::new());
+
+    writer.write_all(b"a").unwrap();
+    writer.write_all(b"bcdefgh").unwrap();
+
+    // the large write bypassed the buffer
+    assert_eq!(writer.buffer(), b"");
+    assert_eq!(writer.get_ref(), b"abcdefgh");
+}
+
+#[test]
+fn short_writes() {
+    let mut writer =
+        BufWriter::with_buffer(Short(Vec::new(), 3), ReadArray::<8>::new_uninit_array());
+
+    writer.write_all(b"abcdefgh").unwrap();
+    writer.write_all(b"i").unwrap();
+    assert_eq!(writer.get_ref().0, b"abcdefgh");
+    assert_eq!(writer.buffer(), b"i");
+
+    writer.flush().unwrap();
+    assert_eq!(writer.get_ref().0, b"abcdefghi");
+}
+
+#[test]
+fn write_zero() {
+    let mut writer =
+        BufWriter::with_buffer(Short(Vec::new(), 0), ReadArray::<4>::new_uninit_array());
+
+    writer.write_all(b"abcd").unwrap();
+    assert_eq!(writer.flush().unwrap_err().kind(), io::ErrorKind::WriteZero);
+
+    // nothing was lost
+    let (e, mut writer) = writer.into_inner().unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(writer.buffer(), b"abcd");
+
+    writer.get_mut().1 = 3;
+    let inner = writer.into_inner().unwrap();
+    assert_eq!(inner.0, b"abcd");
+}
+
+#[test]
+fn drop_flushes() {
+    let mut out = Vec::new();
+    {
+        let mut writer = BufWriter::with_buffer(&mut out, ReadArray::<4>::new_uninit_array());
+        writer.write_all(b"abc").unwrap();
+    }
+    assert_eq!(out, b"abc");
+}
+
+#[test]
+fn into_parts() {
+    let mut writer = BufWriter::with_buffer(Vec::new(), ReadArray::<4>::new_uninit_array());
+    writer.write_all(b"abc").unwrap();
+
+    let (inner, buf) = writer.into_parts();
+    assert_eq!(inner, b"");
+    assert_eq!(buf.unconsumed(), b"abc");
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "bytes")]
+
+use bytes::{Buf, BufMut, BytesMut};
+use cl_generic_read_buf::{bytes::ReadBytesMut, Read, ReadArray, ReadVec};
+
+#[test]
+fn buf_mut() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    assert_eq!(buf.remaining_mut(), 16);
+
+    buf.put_u16(0x0102);
+    buf.put_slice(b"abc");
+    buf.put_bytes(0, 2);
+    assert_eq!(buf.filled(), b"\x01\x02abc\0\0");
+    assert_eq!(buf.initialized_len(), 7);
+    assert_eq!(buf.remaining_mut(), 9);
+}
+
+#[test]
+fn buf_mut_keeps_init() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    buf.initialize_unfilled_to(8);
+
+    buf.put_u8(1);
+    assert_eq!(buf.filled(), [1]);
+    assert_eq!(buf.initialized_len(), 8);
+    assert_eq!(buf.chunk_mut().len(), 15);
+}
+
+#[test]
+fn buf_mut_ref() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    let mut rbuf = buf.borrow();
+    rbuf.put_u32_le(7);
+    rbuf.put_slice(b"abc");
+    assert_eq!(buf.filled(), b"\x07\0\0\0abc");
+}
+
+#[test]
+#[should_panic]
+fn buf_mut_overflow() {
+    let mut buf = ReadVec::from(Vec::with_capacity(2));
+    buf.put_slice(b"abc");
+}
+
+#[test]
+fn buf() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    buf.append(b"\x01\x02abcd");
+
+    assert_eq!(buf.get_u16(), 0x0102);
+    assert_eq!(Buf::remaining(&buf), 4);
+    assert_eq!(buf.chunk(), b"abcd");
+
+    buf.advance(2);
+    assert_eq!(buf.unconsumed(), b"cd");
+    assert_eq!(buf.consumed_len(), 4);
+    assert_eq!(buf.filled(), b"\x01\x02abcd");
+}
+
+#[test]
+fn bytes_mut_storage() {
+    let mut bytes = BytesMut::with_capacity(16);
+    bytes.extend_from_slice(b"init");
+
+    let mut buf = ReadBytesMut::from(bytes);
+    assert_eq!(buf.filled_len(), 0);
+    assert_eq!(buf.initialized_len(), 4);
+    assert!(buf.capacity() >= 16);
+
+    let mut reader = &b"hello world"[..];
+    reader.read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), b"hello world");
+
+    let bytes = BytesMut::from(buf).freeze();
+    assert_eq!(bytes, &b"hello world"[..]);
+}
+
+#[test]
+fn bytes_mut_storage_keeps_allocation() {
+    let bytes = BytesMut::with_capacity(16);
+    let ptr = bytes.as_ptr();
+
+    let mut buf = ReadBytesMut::from(bytes);
+    buf.append(b"abc");
+    buf.initialize_unfilled();
+
+    let bytes = BytesMut::from(buf);
+    assert_eq!(bytes.as_ptr(), ptr);
+    assert_eq!(bytes, &b"abc"[..]);
+}
+
+#[test]
+fn bytes_mut_keeps_consumed() {
+    let mut buf = ReadBytesMut::from(BytesMut::with_capacity(16));
+    buf.append(b"hello world");
+    buf.consume(6);
+
+    let consumed = buf.consumed_len();
+    let mut bytes = BytesMut::from(buf);
+    assert_eq!(bytes, &b"hello world"[..]);
+
+    bytes.advance(consumed);
+    assert_eq!(bytes, &b"world"[..]);
+}
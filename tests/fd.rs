@@ -0,0 +1,133 @@
+#![cfg(unix)]
+
+use cl_generic_read_buf::{Read, ReadArray, ReadVec, RingVec};
+
+use std::{
+    fs,
+    io::Write,
+    net::{TcpListener, TcpStream},
+    os::unix::net::UnixStream,
+    process::{Command, Stdio},
+};
+
+#[test]
+fn file() {
+    let path = std::env::temp_dir().join(format!("generic-read-buf-{}", std::process::id()));
+    fs::write(&path, b"hello world").unwrap();
+    let mut file = fs::File::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let mut buf = ReadVec::from(Vec::with_capacity(64));
+    file.read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), b"hello world");
+    // the kernel wrote straight into the buffer, nothing else was initialized
+    assert_eq!(buf.initialized_len(), 11);
+
+    buf.clear();
+    (&file).read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled_len(), 0);
+}
+
+#[test]
+fn file_read_to_end() {
+    let data: Vec<u8> = (0..100_000).map(|i| i as u8).collect();
+    let path = std::env::temp_dir().join(format!("generic-read-buf-end-{}", std::process::id()));
+    fs::write(&path, &data).unwrap();
+    let mut file = fs::File::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let mut buf = ReadVec::from(Vec::with_capacity(0));
+    let n = file.read_buf_to_end(&mut buf, None).unwrap();
+    assert_eq!(n, data.len());
+    assert_eq!(buf.filled(), data);
+}
+
+#[test]
+fn socketpair() {
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+    a.write_all(b"ping").unwrap();
+
+    let mut buf = ReadVec::from(Vec::with_capacity(64));
+    b.read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), b"ping");
+    assert_eq!(buf.initialized_len(), 4);
+
+    drop(a);
+    (&b).read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), b"ping");
+}
+
+#[test]
+fn tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    client.write_all(b"hello").unwrap();
+    drop(client);
+
+    let mut buf = ReadArray::<5>::new_uninit_array();
+    server.read_buf_exact(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), b"hello");
+    assert_eq!(buf.initialized_len(), 5);
+}
+
+#[test]
+fn pipe() {
+    let mut child = Command::new("sh")
+        .args(["-c", "printf 'out'; printf 'err' >&2"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut buf = ReadVec::from(Vec::with_capacity(64));
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_buf_to_end(&mut buf, None)
+        .unwrap();
+    assert_eq!(buf.filled(), b"out");
+
+    let mut buf = ReadVec::from(Vec::with_capacity(64));
+    child
+        .stderr
+        .take()
+        .unwrap()
+        .read_buf_to_end(&mut buf, None)
+        .unwrap();
+    assert_eq!(buf.filled(), b"err");
+
+    child.wait().unwrap();
+}
+
+#[test]
+fn socketpair_vectored() {
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+    a.write_all(b"headbody").unwrap();
+
+    let mut header = ReadArray::<4>::new_uninit_array();
+    let mut body = ReadVec::from(Vec::with_capacity(64));
+    b.read_buf_vectored(&mut [header.borrow(), body.borrow()])
+        .unwrap();
+    assert_eq!(header.filled(), b"head");
+    assert_eq!(body.filled(), b"body");
+    // the kernel wrote straight into both buffers, nothing else was initialized
+    assert_eq!(header.initialized_len(), 4);
+    assert_eq!(body.initialized_len(), 4);
+}
+
+#[test]
+fn ring_wrapped() {
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+    let mut ring = RingVec::from(ReadVec::from(Vec::with_capacity(8)));
+
+    a.write_all(b"abcdef").unwrap();
+    ring.read_from(&mut b).unwrap();
+    ring.consume(4);
+
+    // the free space wraps around the end of the storage, so both segments are read into with one `readv(2)`
+    a.write_all(b"ghijkl").unwrap();
+    assert_eq!(ring.read_from(&mut b).unwrap(), 6);
+    assert_eq!(ring.filled(), (&b"efgh"[..], &b"ijkl"[..]));
+}
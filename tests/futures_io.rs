@@ -0,0 +1,109 @@
+#![cfg(feature = "futures-io")]
+
+use cl_generic_read_buf::{
+    futures_io::{AsyncReadBufExt, Compat},
+    AsyncReadExt, ReadArray, ReadVec,
+};
+
+use futures::{
+    executor::block_on,
+    future::{poll_fn, FutureExt},
+    io::{AsyncRead, AsyncReadExt as _, Cursor},
+};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[test]
+fn read_buf() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    let mut c = Cursor::new(&b"123"[..]);
+    block_on(c.read_buf(buf.borrow())).unwrap();
+    assert_eq!(buf.filled(), b"123");
+    assert_eq!(buf.initialized_len(), 16);
+
+    // only a window of a large buffer is initialized
+    let mut buf = ReadVec::from(Vec::with_capacity(1 << 20));
+    let mut c = Cursor::new(&b"123"[..]);
+    block_on(c.read_buf(buf.borrow())).unwrap();
+    assert_eq!(buf.filled(), b"123");
+    assert_eq!(buf.initialized_len(), 8 * 1024);
+}
+
+#[test]
+fn read_buf_exact() {
+    let mut buf = ReadArray::<4>::new_uninit_array();
+
+    let mut c = Cursor::new(&b"123456"[..]);
+    block_on(c.read_buf_exact(buf.borrow())).unwrap();
+    assert_eq!(buf.filled(), b"1234");
+
+    buf.clear();
+    let err = block_on(c.read_buf_exact(buf.borrow())).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(buf.filled(), b"56");
+}
+
+#[test]
+fn read_buf_to_end() {
+    let mut buf = ReadVec::from(Vec::new());
+
+    let mut c = Cursor::new(vec![1; 1000]);
+    assert_eq!(block_on(c.read_buf_to_end(&mut buf, None)).unwrap(), 1000);
+    assert_eq!(buf.filled(), [1; 1000]);
+
+    let mut buf = ReadVec::from(Vec::new());
+    let mut c = Cursor::new(vec![1; 101]);
+    let err = block_on(c.read_buf_to_end(&mut buf, Some(100))).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+/// A reader that returns a single byte, and then is pending forever
+struct Once(bool);
+
+impl AsyncRead for Once {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if std::mem::replace(&mut self.0, true) {
+            Poll::Pending
+        } else {
+            buf[0] = 1;
+            Poll::Ready(Ok(1))
+        }
+    }
+}
+
+#[test]
+fn cancellation() {
+    let mut buf = ReadArray::<4>::new_uninit_array();
+    let mut reader = Once(false);
+
+    {
+        let mut fut = reader.read_buf_exact(buf.borrow());
+        let poll = block_on(poll_fn(|cx| Poll::Ready(fut.poll_unpin(cx))));
+        assert!(poll.is_pending());
+    }
+
+    // the byte read before the future was dropped is kept
+    assert_eq!(buf.filled(), [1]);
+}
+
+#[test]
+fn compat() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    let mut reader = Compat::new(Cursor::new(&b"123"[..]));
+    block_on(AsyncReadExt::read_buf(&mut reader, buf.borrow())).unwrap();
+    assert_eq!(buf.filled(), b"123");
+
+    // and back to futures-io again
+    let mut reader = Compat::new(Compat::new(Cursor::new(&b"123"[..])));
+    let mut out = Vec::new();
+    block_on(reader.read_to_end(&mut out)).unwrap();
+    assert_eq!(out, b"123");
+}
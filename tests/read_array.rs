@@ -171,3 +171,63 @@ fn filled_mut() {
 
     assert_eq!(&*filled, &*rbuf.filled_mut());
 }
+
+#[test]
+fn consume() {
+    let buf = [0; 16];
+    let mut rbuf = ReadArray::from(buf);
+
+    rbuf.append(&[1, 2, 3, 4]);
+    rbuf.consume(1);
+
+    assert_eq!(rbuf.consumed_len(), 1);
+    assert_eq!(rbuf.unconsumed(), [2, 3, 4]);
+
+    rbuf.consume(3);
+
+    assert_eq!(rbuf.consumed_len(), 4);
+    assert_eq!(rbuf.unconsumed(), []);
+
+    rbuf.append(&[5, 6]);
+
+    assert_eq!(rbuf.unconsumed(), [5, 6]);
+
+    rbuf.set_filled(2);
+
+    assert_eq!(rbuf.consumed_len(), 2);
+    assert_eq!(rbuf.unconsumed(), []);
+
+    rbuf.clear();
+
+    assert_eq!(rbuf.consumed_len(), 0);
+}
+
+#[test]
+#[should_panic]
+fn consume_panic() {
+    let buf = [0; 16];
+    let mut rbuf = ReadArray::from(buf);
+
+    rbuf.add_filled(4);
+    rbuf.consume(5);
+}
+
+#[test]
+fn compact() {
+    let mut rbuf = ReadArray::<16>::new_uninit_array();
+
+    rbuf.append(&[1, 2, 3, 4, 5, 6]);
+    rbuf.initialize_unfilled_to(4);
+    rbuf.consume(4);
+
+    rbuf.compact();
+
+    assert_eq!(rbuf.consumed_len(), 0);
+    assert_eq!(rbuf.filled(), [5, 6]);
+    assert_eq!(rbuf.initialized_len(), 10);
+    assert_eq!(rbuf.remaining(), 14);
+
+    rbuf.compact();
+
+    assert_eq!(rbuf.filled(), [5, 6]);
+}
@@ -1,6 +1,12 @@
-use cl_generic_read_buf::{Bytes, Read, ReadArray, ReadBuf};
+use cl_generic_read_buf::{
+    BufReader, Bytes, InvalidUtf8, Read, ReadArray, ReadBuf, ReadBufRef, ReadVec, Reader,
+    TrustedRead, TrustedReader,
+};
 
-use std::io::{self, Cursor};
+use std::{
+    collections::VecDeque,
+    io::{self, Cursor},
+};
 
 fn read_buf_exact(mut buf: ReadBuf<impl Bytes>) {
     assert_eq!(buf.capacity(), 4);
@@ -44,3 +50,441 @@ fn read_vec_exact() {
 fn read_array_exact() {
     read_buf_exact(ReadArray::<4>::new_uninit_array())
 }
+
+/// A reader that fills the buffer without ever initializing the unfilled region first
+struct Uninit(u8);
+
+impl io::Read for Uninit {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        buf.fill(self.0);
+        Ok(buf.len())
+    }
+}
+
+impl Read for Uninit {
+    fn read_buf(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        buf.append(&[self.0; 2]);
+        Ok(())
+    }
+}
+
+#[test]
+fn read_buf_override() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    Uninit(7).read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), [7; 2]);
+    assert_eq!(buf.initialized_len(), 2);
+
+    // forwarding impls keep the override
+    let mut reader = Uninit(8);
+    Read::read_buf(&mut &mut reader, buf.borrow()).unwrap();
+    Box::new(Uninit(9)).read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), [7, 7, 8, 8, 9, 9]);
+    assert_eq!(buf.initialized_len(), 6);
+}
+
+#[test]
+fn read_buf_dyn() {
+    let mut array = ReadArray::<16>::new_uninit_array();
+    let mut vec = ReadVec::from(Vec::with_capacity(16));
+
+    // the storage type is erased, so any buffer can be read into a trait object
+    let reader: &mut dyn Read = &mut Uninit(7);
+    Read::read_buf(reader, array.borrow()).unwrap();
+    Read::read_buf(reader, vec.borrow()).unwrap();
+    assert_eq!(array.filled(), [7; 2]);
+    assert_eq!(array.initialized_len(), 2);
+    assert_eq!(vec.filled(), [7; 2]);
+    assert_eq!(vec.initialized_len(), 2);
+}
+
+#[test]
+fn read_buf_boxed_dyn() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    let mut readers: Vec<Box<dyn Read + Send>> = vec![Box::new(Uninit(7)), Box::new(Uninit(8))];
+    for reader in &mut readers {
+        Read::read_buf(reader, buf.borrow()).unwrap();
+    }
+    assert_eq!(buf.filled(), [7, 7, 8, 8]);
+    assert_eq!(buf.initialized_len(), 4);
+
+    // wrappers keep the override too
+    let mut reader = BufReader::with_capacity(4, readers.pop().unwrap());
+    Read::read_buf(&mut &mut reader, buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), [7, 7, 8, 8, 8, 8]);
+    assert_eq!(buf.initialized_len(), 6);
+}
+
+/// A reader that can only be read from with `read_buf_exact`
+struct ExactOnly;
+
+impl io::Read for ExactOnly {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+impl Read for ExactOnly {
+    fn read_buf_exact(&mut self, mut buf: ReadBufRef<'_>) -> io::Result<()> {
+        let n = buf.remaining();
+        buf.initialize_unfilled().fill(1);
+        buf.add_filled(n);
+        Ok(())
+    }
+}
+
+#[test]
+fn forwarding_read_buf_exact() {
+    let mut buf = ReadArray::<4>::new_uninit_array();
+
+    let mut reader: Box<dyn Read> = Box::new(ExactOnly);
+    Read::read_buf_exact(&mut &mut reader, buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), [1; 4]);
+}
+
+#[test]
+fn reader_default() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    Reader::new(Uninit(7)).read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), [7; 16]);
+    assert_eq!(buf.initialized_len(), 16);
+}
+
+#[test]
+fn read_buf_in_memory() {
+    // in-memory readers append without initializing the rest of the buffer
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    let mut slice = &b"hello"[..];
+    slice.read_buf(buf.borrow()).unwrap();
+    assert_eq!(slice, b"");
+    assert_eq!(buf.filled(), b"hello");
+    assert_eq!(buf.initialized_len(), 5);
+
+    let mut c = Cursor::new(b" world");
+    c.set_position(1);
+    c.read_buf(buf.borrow()).unwrap();
+    assert_eq!(c.position(), 6);
+    assert_eq!(buf.filled(), b"helloworld");
+    assert_eq!(buf.initialized_len(), 10);
+
+    c.set_position(100);
+    c.read_buf(buf.borrow()).unwrap();
+    assert_eq!(c.position(), 6);
+
+    let mut deque = VecDeque::from(b"!!".to_vec());
+    deque.push_front(b'?');
+    deque.extend(b"1234567");
+    deque.read_buf(buf.borrow()).unwrap();
+    assert_eq!(deque, b"4567");
+    assert_eq!(buf.filled(), b"helloworld?!!123");
+
+    buf.clear();
+    io::empty().read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled_len(), 0);
+
+    io::repeat(3).read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), [3; 16]);
+}
+
+/// Writes up to 3 bytes without looking at the buffer
+struct Counter(u8);
+
+impl io::Read for Counter {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(3);
+        for b in &mut buf[..n] {
+            *b = self.0;
+            self.0 += 1;
+        }
+        Ok(n)
+    }
+}
+
+unsafe impl TrustedRead for Counter {}
+
+#[test]
+fn trusted_reader() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    let mut reader = TrustedReader::new(Counter(0));
+    reader.read_buf(buf.borrow()).unwrap();
+    reader.read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), [0, 1, 2, 3, 4, 5]);
+    assert_eq!(buf.initialized_len(), 6);
+
+    reader.read_buf_exact(buf.borrow()).unwrap();
+    assert_eq!(buf.filled_len(), 16);
+    assert_eq!(reader.into_inner().0, 16);
+}
+
+/// Breaks the `TrustedRead` contract by copying out of the buffer
+#[cfg(debug_assertions)]
+struct Peek;
+
+#[cfg(debug_assertions)]
+impl io::Read for Peek {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        buf[0] = buf[buf.len() - 1];
+        Ok(1)
+    }
+}
+
+#[cfg(debug_assertions)]
+unsafe impl TrustedRead for Peek {}
+
+#[test]
+#[cfg(debug_assertions)]
+fn trusted_reader_poison() {
+    let mut buf = ReadArray::<4>::new_uninit_array();
+
+    TrustedReader::new(Peek).read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled(), [0xA5]);
+}
+
+/// Breaks the `TrustedRead` contract by writing past the bytes it returns, using the whole buffer as scratch space
+#[cfg(debug_assertions)]
+struct Scratch;
+
+#[cfg(debug_assertions)]
+impl io::Read for Scratch {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        buf.fill(0);
+        Ok(1)
+    }
+}
+
+#[cfg(debug_assertions)]
+unsafe impl TrustedRead for Scratch {}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "TrustedRead reader modified the buffer past the bytes it returned"]
+fn trusted_reader_poison_check() {
+    let mut buf = ReadArray::<4>::new_uninit_array();
+
+    TrustedReader::new(Scratch).read_buf(buf.borrow()).unwrap();
+}
+
+/// Delivers at most 1500 bytes per read, like packets from a socket
+struct Packets;
+
+impl io::Read for Packets {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(1500);
+        buf[..n].fill(3);
+        Ok(n)
+    }
+}
+
+#[test]
+fn reader_default_window() {
+    let mut buf = ReadVec::from(Vec::with_capacity(1 << 20));
+
+    // short reads only initialize a small window
+    let mut short = Reader::new(io::Read::take(Uninit(1), 1500));
+    short.read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled_len(), 1500);
+    assert_eq!(buf.initialized_len(), 8 * 1024);
+
+    // reads that fill the window double it, without initializing the next window up front
+    let mut buf = ReadVec::from(Vec::with_capacity(1 << 20));
+    let mut full = Reader::new(Uninit(2));
+    let mut filled = 0;
+    for window in [8192, 16384, 32768, 65536] {
+        full.read_buf(buf.borrow()).unwrap();
+        filled += window;
+        assert_eq!(buf.filled_len(), filled);
+        assert_eq!(buf.initialized_len(), filled);
+    }
+
+    // a cleared buffer keeps its window
+    buf.clear();
+    full.read_buf(buf.borrow()).unwrap();
+    assert_eq!(buf.filled_len(), 131072);
+}
+
+#[test]
+fn reader_default_window_after_short_reads() {
+    let mut buf = ReadVec::from(Vec::with_capacity(4 << 20));
+
+    let mut packets = Reader::new(Packets);
+    while buf.filled_len() < 1 << 20 {
+        packets.read_buf(buf.borrow()).unwrap();
+        assert!(buf.initialized_len() - buf.filled_len() <= 8 * 1024);
+    }
+
+    // a read that fills the window only doubles it, however much was read before
+    let mut full = Reader::new(Uninit(2));
+    for window in [8192, 16384, 32768] {
+        let filled = buf.filled_len();
+        full.read_buf(buf.borrow()).unwrap();
+        assert_eq!(buf.filled_len() - filled, window);
+        assert_eq!(buf.initialized_len(), buf.filled_len());
+    }
+}
+
+#[test]
+fn read_buf_grow() {
+    let mut buf = ReadVec::from(Vec::new());
+    let mut c = Cursor::new(vec![1; 100]);
+
+    while buf.filled_len() < 100 {
+        c.read_buf_grow(&mut buf).unwrap();
+    }
+
+    assert_eq!(buf.filled(), [1; 100]);
+}
+
+#[test]
+fn read_buf_vectored() {
+    // buffers with different storage can be read into together
+    let mut header = ReadArray::<4>::new_uninit_array();
+    let mut body = ReadVec::from(Vec::with_capacity(8));
+    body.append(b"ab");
+
+    let mut c = Cursor::new(&b"123456789"[..]);
+    c.read_buf_vectored(&mut [header.borrow(), body.borrow()])
+        .unwrap();
+
+    assert_eq!(header.filled(), b"1234");
+    assert_eq!(body.filled(), b"ab56789");
+
+    // a short read only fills the first buffer
+    header.clear();
+    body.clear();
+    let mut c = Cursor::new(&b"12"[..]);
+    c.read_buf_vectored(&mut [header.borrow(), body.borrow()])
+        .unwrap();
+
+    assert_eq!(header.filled(), b"12");
+    assert_eq!(body.filled(), b"");
+}
+
+#[test]
+fn read_buf_to_end() {
+    let mut buf = ReadVec::from(Vec::new());
+    let mut c = Cursor::new(vec![1; 1000]);
+
+    assert_eq!(c.read_buf_to_end(&mut buf, None).unwrap(), 1000);
+    assert_eq!(buf.filled(), [1; 1000]);
+    // cursors append their bytes, so the spare capacity is never initialized
+    assert_eq!(buf.initialized_len(), 1000);
+
+    // appends to the existing contents
+    let mut c = Cursor::new(vec![2; 10]);
+    assert_eq!(c.read_buf_to_end(&mut buf, None).unwrap(), 10);
+    assert_eq!(buf.filled_len(), 1010);
+    assert_eq!(buf.filled()[1000..], [2; 10]);
+}
+
+#[test]
+fn read_buf_to_end_limit() {
+    let mut buf = ReadVec::from(Vec::new());
+    let mut c = Cursor::new(vec![1; 100]);
+    assert_eq!(c.read_buf_to_end(&mut buf, Some(100)).unwrap(), 100);
+    assert_eq!(buf.filled(), [1; 100]);
+    assert_eq!(buf.capacity(), 100);
+
+    let mut buf = ReadVec::from(Vec::new());
+    let mut c = Cursor::new(vec![1; 101]);
+    assert_eq!(
+        c.read_buf_to_end(&mut buf, Some(100)).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    assert_eq!(buf.filled(), [1; 100]);
+
+    // a larger existing buffer can not be used to go over the limit
+    let mut buf = ReadVec::from(Vec::with_capacity(1 << 20));
+    let mut c = Cursor::new(vec![1; 1 << 20]);
+    assert_eq!(
+        c.read_buf_to_end(&mut buf, Some(1024)).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    assert!(buf.filled_len() <= 1025);
+
+    // nor can a reader that zeroes its window
+    let mut buf = ReadVec::from(Vec::with_capacity(1 << 20));
+    let mut reader = Reader::new(Uninit(1));
+    assert_eq!(
+        reader
+            .read_buf_to_end(&mut buf, Some(1024))
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::InvalidData
+    );
+    assert!(buf.filled_len() <= 1025);
+    assert!(buf.initialized_len() <= 1025);
+}
+
+/// A reader that is interrupted before every successful read
+struct Interrupting<R>(R, bool);
+
+impl<R: io::Read> io::Read for Interrupting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.1 = !self.1;
+        if self.1 {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        self.0.read(buf)
+    }
+}
+
+impl<R: io::Read> Read for Interrupting<R> {}
+
+#[test]
+fn read_buf_to_end_interrupted() {
+    let mut buf = ReadVec::from(Vec::new());
+    let mut r = Interrupting(Cursor::new(vec![1; 100]), false);
+
+    assert_eq!(r.read_buf_to_end(&mut buf, Some(100)).unwrap(), 100);
+    assert_eq!(buf.filled(), [1; 100]);
+}
+
+/// A reader that returns at most `n` bytes per read
+struct Chunked<R>(R, usize);
+
+impl<R: io::Read> io::Read for Chunked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.1);
+        self.0.read(&mut buf[..n])
+    }
+}
+
+impl<R: io::Read> Read for Chunked<R> {}
+
+#[test]
+fn read_buf_to_string() {
+    let text = "héllo wörld ✨";
+    let mut s = String::from("> ");
+
+    // split the multi-byte sequences across reads
+    let mut r = Chunked(Cursor::new(text), 1);
+    assert_eq!(r.read_buf_to_string(&mut s, None).unwrap(), text.len());
+    assert_eq!(s, format!("> {text}"));
+}
+
+#[test]
+fn read_buf_to_string_invalid() {
+    let mut s = String::from("> ");
+
+    let mut r = Chunked(Cursor::new(&b"abc\xffdef"[..]), 2);
+    let err = r.read_buf_to_string(&mut s, None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let inner = err.get_ref().unwrap().downcast_ref::<InvalidUtf8>();
+    assert_eq!(inner.unwrap().offset(), 3);
+    assert_eq!(s, "> ");
+
+    // fails as soon as the invalid sequence is read
+    assert_eq!(r.0.position(), 4);
+
+    // an incomplete sequence at EOF
+    let mut r = Cursor::new(&"ab✨".as_bytes()[..4]);
+    let err = r.read_buf_to_string(&mut s, None).unwrap_err();
+    let inner = err.get_ref().unwrap().downcast_ref::<InvalidUtf8>();
+    assert_eq!(inner.unwrap().offset(), 2);
+    assert_eq!(s, "> ");
+}
@@ -103,6 +103,66 @@ fn set_filled_panic() {
     rbuf.set_filled(16);
 }
 
+#[test]
+fn try_initialize_unfilled_to() {
+    let buf = Vec::with_capacity(16);
+    let mut rbuf = ReadVec::from(buf);
+
+    let err = rbuf.try_initialize_unfilled_to(17).unwrap_err();
+
+    assert_eq!(err.requested(), 17);
+    assert_eq!(err.available(), 16);
+    assert_eq!(rbuf.initialized_len(), 0);
+
+    assert_eq!(rbuf.try_initialize_unfilled_to(16).unwrap().len(), 16);
+    assert_eq!(rbuf.initialized_len(), 16);
+}
+
+#[test]
+fn try_add_filled() {
+    let buf = Vec::with_capacity(16);
+    let mut rbuf = ReadVec::from(buf);
+
+    let err = rbuf.try_add_filled(1).unwrap_err();
+
+    assert_eq!(err.requested(), 1);
+    assert_eq!(err.available(), 0);
+    assert_eq!(rbuf.filled_len(), 0);
+
+    rbuf.initialize_unfilled_to(4);
+    rbuf.try_add_filled(4).unwrap();
+
+    assert_eq!(rbuf.filled_len(), 4);
+
+    // a length field that would overflow is still an error
+    let err = rbuf.try_add_filled(usize::MAX).unwrap_err();
+
+    assert_eq!(err.requested(), usize::MAX);
+    assert_eq!(err.available(), 4);
+    assert_eq!(rbuf.filled_len(), 4);
+}
+
+#[test]
+fn try_set_filled() {
+    let buf = vec![0; 8];
+    let mut rbuf = ReadVec::from(buf);
+    rbuf.reserve(8);
+
+    let err = rbuf.try_set_filled(9).unwrap_err();
+
+    assert_eq!(err.requested(), 9);
+    assert_eq!(err.available(), 8);
+    assert_eq!(
+        err.to_string(),
+        "requested 9 filled bytes but only 8 bytes are initialized"
+    );
+    assert_eq!(rbuf.filled_len(), 0);
+
+    rbuf.try_set_filled(8).unwrap();
+
+    assert_eq!(rbuf.filled_len(), 8);
+}
+
 #[test]
 fn clear() {
     let buf = vec![255; 16];
@@ -168,6 +228,28 @@ fn append() {
     assert_eq!(rbuf.filled(), [1; 16]);
 }
 
+#[test]
+fn try_append() {
+    let buf = Vec::with_capacity(16);
+    let mut rbuf = ReadVec::from(buf);
+
+    rbuf.try_append(&[0; 8]).unwrap();
+    let err = rbuf.try_append(&[1; 9]).unwrap_err();
+
+    assert_eq!(err.requested(), 9);
+    assert_eq!(err.available(), 8);
+    assert_eq!(
+        err.to_string(),
+        "requested 9 bytes but only 8 bytes of capacity remain"
+    );
+    assert_eq!(rbuf.initialized_len(), 8);
+    assert_eq!(rbuf.filled(), [0; 8]);
+
+    rbuf.borrow().try_append(&[1; 8]).unwrap();
+
+    assert_eq!(rbuf.filled_len(), 16);
+}
+
 #[test]
 fn filled_mut() {
     let buf = vec![0; 16];
@@ -179,3 +261,147 @@ fn filled_mut() {
 
     assert_eq!(&*filled, &*rbuf.filled_mut());
 }
+
+#[test]
+fn reserve() {
+    let mut buf = vec![1; 16];
+    buf.truncate(8);
+    let mut rbuf = ReadVec::from(buf);
+    rbuf.add_filled(4);
+
+    rbuf.reserve(32);
+
+    assert!(rbuf.remaining() >= 32);
+    assert_eq!(rbuf.filled(), [1; 4]);
+    assert_eq!(rbuf.filled_len(), 4);
+    assert_eq!(rbuf.initialized_len(), 8);
+}
+
+#[test]
+fn reserve_exact() {
+    let buf = vec![1; 8];
+    let mut rbuf = ReadVec::from(buf);
+    rbuf.add_filled(4);
+
+    // already enough room
+    rbuf.reserve_exact(4);
+    assert_eq!(rbuf.capacity(), 8);
+
+    rbuf.reserve_exact(12);
+    assert_eq!(rbuf.capacity(), 16);
+    assert_eq!(rbuf.remaining(), 12);
+    assert_eq!(rbuf.filled(), [1; 4]);
+    assert_eq!(rbuf.initialized_len(), 8);
+}
+
+#[test]
+fn try_reserve() {
+    let buf = vec![1; 8];
+    let mut rbuf = ReadVec::from(buf);
+    rbuf.add_filled(4);
+
+    rbuf.try_reserve(16).unwrap();
+    assert!(rbuf.remaining() >= 16);
+
+    let capacity = rbuf.capacity();
+    rbuf.try_reserve(usize::MAX).unwrap_err();
+    assert_eq!(rbuf.capacity(), capacity);
+    assert_eq!(rbuf.filled(), [1; 4]);
+    assert_eq!(rbuf.initialized_len(), 8);
+}
+
+#[test]
+fn consume() {
+    let buf = vec![0; 16];
+    let mut rbuf = ReadVec::from(buf);
+
+    rbuf.append(&[1, 2, 3, 4]);
+    rbuf.consume(1);
+
+    assert_eq!(rbuf.consumed_len(), 1);
+    assert_eq!(rbuf.unconsumed(), [2, 3, 4]);
+
+    rbuf.consume(3);
+
+    assert_eq!(rbuf.consumed_len(), 4);
+    assert_eq!(rbuf.unconsumed(), []);
+
+    rbuf.append(&[5, 6]);
+
+    assert_eq!(rbuf.unconsumed(), [5, 6]);
+
+    rbuf.set_filled(2);
+
+    assert_eq!(rbuf.consumed_len(), 2);
+    assert_eq!(rbuf.unconsumed(), []);
+
+    rbuf.clear();
+
+    assert_eq!(rbuf.consumed_len(), 0);
+}
+
+#[test]
+fn try_consume() {
+    let buf = vec![0; 16];
+    let mut rbuf = ReadVec::from(buf);
+
+    rbuf.add_filled(4);
+    rbuf.try_consume(1).unwrap();
+    let err = rbuf.try_consume(4).unwrap_err();
+
+    assert_eq!(err.requested(), 4);
+    assert_eq!(err.available(), 3);
+    assert_eq!(
+        err.to_string(),
+        "requested to consume 4 bytes but only 3 bytes are unconsumed"
+    );
+    assert_eq!(rbuf.consumed_len(), 1);
+
+    rbuf.try_consume(3).unwrap();
+
+    assert_eq!(rbuf.unconsumed(), []);
+}
+
+#[test]
+#[should_panic]
+fn consume_panic() {
+    let buf = vec![0; 16];
+    let mut rbuf = ReadVec::from(buf);
+
+    rbuf.add_filled(4);
+    rbuf.consume(5);
+}
+
+#[test]
+fn compact() {
+    let buf = Vec::with_capacity(16);
+    let mut rbuf = ReadVec::from(buf);
+
+    rbuf.append(&[1, 2, 3, 4, 5, 6]);
+    rbuf.initialize_unfilled_to(4);
+    rbuf.consume(4);
+
+    rbuf.compact();
+
+    assert_eq!(rbuf.consumed_len(), 0);
+    assert_eq!(rbuf.filled(), [5, 6]);
+    assert_eq!(rbuf.initialized_len(), 10);
+    assert_eq!(rbuf.remaining(), 14);
+
+    rbuf.compact();
+
+    assert_eq!(rbuf.filled(), [5, 6]);
+}
+
+#[test]
+fn into_vec_keeps_consumed() {
+    let mut rbuf = ReadVec::from(Vec::with_capacity(16));
+
+    rbuf.append(b"hello world");
+    rbuf.initialize_unfilled();
+    rbuf.consume(6);
+
+    let vec = Vec::from(rbuf);
+    assert_eq!(vec, b"hello world");
+    assert_eq!(vec.capacity(), 16);
+}
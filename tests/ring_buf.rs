@@ -0,0 +1,173 @@
+use cl_generic_read_buf::{ReadVec, RingArray, RingBuf};
+
+use std::io::{self, Cursor};
+
+/// Test that RingBuf has the correct numbers when created from an uninitialised array
+#[test]
+fn uninit() {
+    let rbuf = RingArray::<16>::new_uninit_array();
+
+    assert_eq!(rbuf.filled_len(), 0);
+    assert_eq!(rbuf.initialized_len(), 0);
+    assert_eq!(rbuf.capacity(), 16);
+    assert_eq!(rbuf.remaining(), 16);
+}
+
+/// Test that RingBuf keeps the unconsumed bytes of a ReadBuf
+#[test]
+fn from_read_buf() {
+    let mut buf = ReadVec::from(Vec::with_capacity(16));
+    buf.append(&[1, 2, 3, 4]);
+    buf.consume(1);
+
+    let rbuf = RingBuf::from(buf);
+
+    assert_eq!(rbuf.filled(), (&[2, 3, 4][..], &[][..]));
+    assert_eq!(rbuf.initialized_len(), 4);
+    assert_eq!(rbuf.remaining(), 13);
+}
+
+/// Test that a fully consumed ReadBuf becomes an empty ring that starts at the front
+#[test]
+fn from_consumed_read_buf() {
+    let mut buf = ReadVec::from(Vec::with_capacity(4));
+    buf.append(&[1, 2, 3, 4]);
+    buf.consume(4);
+
+    let mut rbuf = RingBuf::from(buf);
+    rbuf.append(&[9, 9]);
+
+    assert_eq!(rbuf.filled(), (&[9, 9][..], &[][..]));
+    assert_eq!(rbuf.remaining(), 2);
+}
+
+#[test]
+fn append_wrap() {
+    let mut rbuf = RingArray::<8>::new_uninit_array();
+
+    rbuf.append(&[1, 2, 3, 4, 5, 6]);
+    rbuf.consume(4);
+
+    assert_eq!(rbuf.filled(), (&[5, 6][..], &[][..]));
+    assert_eq!(rbuf.remaining(), 6);
+
+    rbuf.append(&[7, 8, 9, 10]);
+
+    assert_eq!(rbuf.filled(), (&[5, 6, 7, 8][..], &[9, 10][..]));
+    assert_eq!(rbuf.initialized_len(), 8);
+    assert_eq!(rbuf.remaining(), 2);
+
+    rbuf.consume(4);
+
+    assert_eq!(rbuf.filled(), (&[9, 10][..], &[][..]));
+
+    rbuf.consume(2);
+
+    assert_eq!(rbuf.filled_len(), 0);
+    assert_eq!(rbuf.remaining(), 8);
+}
+
+#[test]
+#[should_panic]
+fn append_panic() {
+    let mut rbuf = RingArray::<8>::new_uninit_array();
+
+    rbuf.append(&[1; 6]);
+    rbuf.consume(2);
+    rbuf.append(&[1; 5]);
+}
+
+#[test]
+fn unfilled_wrap() {
+    let mut rbuf = RingArray::<8>::new_uninit_array();
+
+    rbuf.append(&[1; 6]);
+    rbuf.consume(4);
+
+    let (tail, wrapped) = unsafe { rbuf.unfilled_mut() };
+    assert_eq!(tail.len(), 2);
+    assert_eq!(wrapped.len(), 4);
+
+    let (tail, wrapped) = rbuf.initialize_unfilled();
+    assert_eq!(tail, [0; 2]);
+    assert_eq!(wrapped, [1; 4]);
+    assert_eq!(rbuf.initialized_len(), 8);
+}
+
+#[test]
+fn add_filled() {
+    let mut rbuf = RingArray::<8>::new_uninit_array();
+
+    rbuf.append(&[1; 4]);
+    rbuf.consume(2);
+
+    unsafe {
+        rbuf.assume_init(6);
+    }
+
+    assert_eq!(rbuf.initialized_len(), 8);
+
+    rbuf.add_filled(6);
+
+    assert_eq!(rbuf.filled_len(), 8);
+    assert_eq!(rbuf.remaining(), 0);
+}
+
+#[test]
+#[should_panic]
+fn add_filled_panic() {
+    let mut rbuf = RingArray::<8>::new_uninit_array();
+
+    rbuf.append(&[1; 4]);
+    rbuf.add_filled(1);
+}
+
+#[test]
+fn clear() {
+    let mut rbuf = RingArray::<8>::new_uninit_array();
+
+    rbuf.append(&[1; 6]);
+    rbuf.clear();
+
+    assert_eq!(rbuf.filled_len(), 0);
+    assert_eq!(rbuf.initialized_len(), 6);
+    assert_eq!(rbuf.remaining(), 8);
+}
+
+#[test]
+fn read_from() {
+    let mut rbuf = RingArray::<8>::new_uninit_array();
+    let mut c = Cursor::new(&b"123456789abc"[..]);
+
+    assert_eq!(rbuf.read_from(&mut c).unwrap(), 8);
+    assert_eq!(rbuf.filled(), (&b"12345678"[..], &[][..]));
+
+    rbuf.consume(6);
+
+    // both unfilled segments are filled by a single vectored read
+    assert_eq!(rbuf.read_from(&mut c).unwrap(), 4);
+    assert_eq!(rbuf.filled(), (&b"78"[..], &b"9abc"[..]));
+
+    assert_eq!(rbuf.read_from(&mut io::empty()).unwrap(), 0);
+    assert_eq!(rbuf.filled_len(), 6);
+}
+
+#[test]
+fn read_from_uninit() {
+    let mut rbuf = RingBuf::from(ReadVec::from(Vec::with_capacity(1024)));
+
+    // slices append without initializing the rest of the ring
+    assert_eq!(rbuf.read_from(&mut &b"abc"[..]).unwrap(), 3);
+    assert_eq!(rbuf.filled(), (&b"abc"[..], &[][..]));
+    assert_eq!(rbuf.initialized_len(), 3);
+
+    rbuf.consume(3);
+    assert_eq!(rbuf.read_from(&mut &b"de"[..]).unwrap(), 2);
+    assert_eq!(rbuf.filled(), (&b"de"[..], &[][..]));
+    assert_eq!(rbuf.initialized_len(), 3);
+
+    // a wrapped ring goes through the vectored read
+    rbuf.consume(1);
+    assert_eq!(rbuf.read_from(&mut &b"fg"[..]).unwrap(), 2);
+    assert_eq!(rbuf.filled(), (&b"efg"[..], &[][..]));
+}
@@ -0,0 +1,152 @@
+#![cfg(feature = "tokio")]
+
+use cl_generic_read_buf::{
+    tokio::{with_read_buf, AsyncReadBufExt, Compat},
+    AsyncReadExt, ReadArray, ReadVec,
+};
+
+use std::{
+    io::{self, Cursor},
+    mem::MaybeUninit,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// A reader that fills the buffer without initializing the rest of it first
+struct Uninit(u8);
+
+impl AsyncRead for Uninit {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        buf.put_slice(&[self.0; 2]);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn read_buf() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    Uninit(7).read_buf(buf.borrow()).await.unwrap();
+    assert_eq!(buf.filled(), [7; 2]);
+    assert_eq!(buf.initialized_len(), 2);
+
+    let mut c = Cursor::new(&b"123"[..]);
+    c.read_buf(buf.borrow()).await.unwrap();
+    assert_eq!(buf.filled(), b"\x07\x07123");
+    assert_eq!(buf.initialized_len(), 5);
+}
+
+#[tokio::test]
+async fn read_buf_keeps_init() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    buf.initialize_unfilled_to(8);
+
+    Uninit(7).read_buf(buf.borrow()).await.unwrap();
+    assert_eq!(buf.filled(), [7; 2]);
+    assert_eq!(buf.initialized_len(), 8);
+}
+
+#[tokio::test]
+async fn read_buf_exact() {
+    let mut buf = ReadArray::<4>::new_uninit_array();
+
+    let mut c = Cursor::new(&b"123456"[..]);
+    c.read_buf_exact(buf.borrow()).await.unwrap();
+    assert_eq!(buf.filled(), b"1234");
+
+    buf.clear();
+    let err = c.read_buf_exact(buf.borrow()).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(buf.filled(), b"56");
+}
+
+#[tokio::test]
+async fn read_buf_to_end() {
+    let mut buf = ReadVec::from(Vec::new());
+
+    let mut c = Cursor::new(vec![1; 1000]);
+    assert_eq!(c.read_buf_to_end(&mut buf, None).await.unwrap(), 1000);
+    assert_eq!(buf.filled(), [1; 1000]);
+
+    let mut buf = ReadVec::from(Vec::new());
+    let mut c = Cursor::new(vec![1; 101]);
+    let err = c.read_buf_to_end(&mut buf, Some(100)).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut buf = ReadVec::from(Vec::new());
+    let mut c = Cursor::new(vec![1; 100]);
+    assert_eq!(c.read_buf_to_end(&mut buf, Some(100)).await.unwrap(), 100);
+}
+
+#[test]
+fn with_tokio() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    buf.append(b"ab");
+    buf.initialize_unfilled_to(4);
+
+    let mut rbuf = buf.borrow();
+    rbuf.with_tokio(|tokio_buf| {
+        assert_eq!(tokio_buf.filled(), b"");
+        assert_eq!(tokio_buf.initialized().len(), 4);
+        assert_eq!(tokio_buf.capacity(), 14);
+
+        tokio_buf.put_slice(b"cd");
+        tokio_buf.initialize_unfilled_to(6);
+    });
+
+    assert_eq!(buf.filled(), b"abcd");
+    assert_eq!(buf.initialized_len(), 10);
+}
+
+#[test]
+#[should_panic]
+fn with_tokio_replaced() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+
+    buf.borrow().with_tokio(|tokio_buf| {
+        *tokio_buf = ReadBuf::uninit(Box::leak(Box::new([MaybeUninit::uninit(); 4])));
+    });
+}
+
+#[test]
+fn with_read_buf_from_tokio() {
+    let mut storage = [MaybeUninit::uninit(); 16];
+    let mut tokio_buf = ReadBuf::uninit(&mut storage);
+    tokio_buf.put_slice(b"ab");
+    tokio_buf.initialize_unfilled_to(2);
+
+    with_read_buf(&mut tokio_buf, |mut buf| {
+        assert_eq!(buf.filled(), b"");
+        assert_eq!(buf.initialized_len(), 2);
+        assert_eq!(buf.capacity(), 14);
+
+        buf.append(b"cdef");
+    });
+
+    assert_eq!(tokio_buf.filled(), b"abcdef");
+    assert_eq!(tokio_buf.initialized().len(), 6);
+}
+
+#[tokio::test]
+async fn compat() {
+    let mut buf = ReadArray::<16>::new_uninit_array();
+    let mut reader = Compat::new(Uninit(7));
+    AsyncReadExt::read_buf_exact(&mut reader, buf.borrow())
+        .await
+        .unwrap();
+    assert_eq!(buf.filled(), [7; 16]);
+    assert_eq!(buf.initialized_len(), 16);
+
+    // and back to tokio again
+    let mut reader = Compat::new(Compat::new(Cursor::new(&b"123"[..])));
+    let mut out = Vec::new();
+    tokio::io::AsyncReadExt::read_to_end(&mut reader, &mut out)
+        .await
+        .unwrap();
+    assert_eq!(out, b"123");
+}